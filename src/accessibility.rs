@@ -0,0 +1,228 @@
+use bevy::{prelude::*, sprite::Mesh2dHandle};
+
+use crate::{
+    boost::{BoostConfig, Pellet},
+    coin::Coin,
+    power_up::{PowerUp, PowerUpConfig, PowerUpKind},
+    settings::Settings,
+    theme::Palette,
+};
+
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_food_meshes).add_systems(
+            Update,
+            (
+                apply_food_shapes,
+                record_base_font_sizes,
+                apply_text_scale,
+                apply_game_speed,
+            ),
+        );
+    }
+}
+
+/// Color overrides on top of the theme for the common kinds of color blindness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorPalette {
+    /// Use the theme's own colors.
+    Theme,
+    /// Blue snake and orange food, safe for protanopia and deuteranopia.
+    RedGreenSafe,
+    /// Teal snake and vermillion food, safe for tritanopia.
+    BlueYellowSafe,
+}
+
+impl ColorPalette {
+    const ALL: [ColorPalette; 3] = [
+        ColorPalette::Theme,
+        ColorPalette::RedGreenSafe,
+        ColorPalette::BlueYellowSafe,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColorPalette::Theme => "Theme",
+            ColorPalette::RedGreenSafe => "Red-Green Safe",
+            ColorPalette::BlueYellowSafe => "Blue-Yellow Safe",
+        }
+    }
+
+    pub fn cycle(&self, step: i32) -> ColorPalette {
+        let index = Self::ALL
+            .iter()
+            .position(|palette| palette == self)
+            .unwrap_or(0) as i32;
+        Self::ALL[(index + step).rem_euclid(Self::ALL.len() as i32) as usize]
+    }
+
+    pub fn apply(&self, palette: &mut Palette) {
+        // Colors from the Okabe-Ito palette, picked to stay apart under each deficiency.
+        let ((head, body), (rival_head, rival_body), coin, pellet, power_ups) = match self {
+            ColorPalette::Theme => return,
+            ColorPalette::RedGreenSafe => (
+                ("#56B4E9", "#0072B2"),
+                ("#CC79A7", "#A0527F"),
+                "#E69F00",
+                "#F0E442",
+                ["#009E73", "#FFFFFF", "#D55E00", "#F0E442"],
+            ),
+            ColorPalette::BlueYellowSafe => (
+                ("#009E73", "#00796B"),
+                ("#EEEEEE", "#BBBBBB"),
+                "#D55E00",
+                "#CC79A7",
+                ["#56B4E9", "#FFFFFF", "#0072B2", "#E69F00"],
+            ),
+        };
+        let color = |hex| Color::hex(hex).unwrap();
+        palette.snake_head = color(head);
+        palette.snake_body = color(body);
+        palette.rival_head = color(rival_head);
+        palette.rival_body = color(rival_body);
+        palette.coin = color(coin);
+        palette.pellet = color(pellet);
+        palette.power_ups = power_ups.map(color);
+    }
+}
+
+/// Black background, white text and fully saturated, bright game colors.
+pub fn apply_high_contrast(palette: &mut Palette) {
+    let brighten = |color: Color| match color.as_hsla() {
+        Color::Hsla { hue, alpha, .. } => Color::hsla(hue, 1.0, 0.6, alpha),
+        other => other,
+    };
+    palette.snake_head = brighten(palette.snake_head);
+    palette.snake_body = brighten(palette.snake_body);
+    palette.rival_head = brighten(palette.rival_head);
+    palette.rival_body = brighten(palette.rival_body);
+    palette.coin = brighten(palette.coin);
+    palette.pellet = brighten(palette.pellet);
+    palette.power_ups = palette.power_ups.map(brighten);
+    palette.background = Color::BLACK;
+    palette.ui_text = Color::WHITE;
+}
+
+/// Meshes for every kind of food, in plain and shape-coded variants.
+#[derive(Resource)]
+pub struct FoodMeshes {
+    coin: Handle<Mesh>,
+    coin_shaped: Handle<Mesh>,
+    pellet: Handle<Mesh>,
+    pellet_shaped: Handle<Mesh>,
+    power_up: Handle<Mesh>,
+    power_up_shaped: [Handle<Mesh>; 4],
+}
+
+impl FoodMeshes {
+    pub fn coin(&self, shape_coded: bool) -> Handle<Mesh> {
+        if shape_coded {
+            self.coin_shaped.clone()
+        } else {
+            self.coin.clone()
+        }
+    }
+
+    pub fn pellet(&self, shape_coded: bool) -> Handle<Mesh> {
+        if shape_coded {
+            self.pellet_shaped.clone()
+        } else {
+            self.pellet.clone()
+        }
+    }
+
+    pub fn power_up(&self, kind: PowerUpKind, shape_coded: bool) -> Handle<Mesh> {
+        if !shape_coded {
+            return self.power_up.clone();
+        }
+        self.power_up_shaped[kind.index()].clone()
+    }
+}
+
+/// Base font size of each section of a UI text, before the text scale is applied.
+#[derive(Component)]
+struct BaseFontSize(Vec<f32>);
+
+fn setup_food_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    boost_config: Res<BoostConfig>,
+    power_up_config: Res<PowerUpConfig>,
+) {
+    let coin_radius = 5.0;
+    let pellet_radius = boost_config.pellet_radius;
+    let power_up_size = power_up_config.pickup_size;
+    let power_up_radius = power_up_size / 2.0;
+
+    commands.insert_resource(FoodMeshes {
+        coin: meshes.add(Circle::new(coin_radius)),
+        coin_shaped: meshes.add(RegularPolygon::new(coin_radius * 1.4, 4)),
+        pellet: meshes.add(Circle::new(pellet_radius)),
+        pellet_shaped: meshes.add(RegularPolygon::new(pellet_radius * 1.5, 3)),
+        power_up: meshes.add(Rectangle::new(power_up_size, power_up_size)),
+        power_up_shaped: [
+            meshes.add(RegularPolygon::new(power_up_radius * 1.2, 5)),
+            meshes.add(RegularPolygon::new(power_up_radius * 1.2, 6)),
+            meshes.add(RegularPolygon::new(power_up_radius * 1.2, 8)),
+            meshes.add(Capsule2d::new(power_up_radius * 0.6, power_up_size)),
+        ],
+    });
+}
+
+fn apply_food_shapes(
+    settings: Res<Settings>,
+    food_meshes: Res<FoodMeshes>,
+    mut coin_query: Query<&mut Mesh2dHandle, (With<Coin>, Without<Pellet>)>,
+    mut pellet_query: Query<&mut Mesh2dHandle, With<Pellet>>,
+    mut power_up_query: Query<(&mut Mesh2dHandle, &PowerUp), (Without<Coin>, Without<Pellet>)>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let shape_coded = settings.shape_coded_food;
+    for mut mesh in coin_query.iter_mut() {
+        mesh.0 = food_meshes.coin(shape_coded);
+    }
+    for mut mesh in pellet_query.iter_mut() {
+        mesh.0 = food_meshes.pellet(shape_coded);
+    }
+    for (mut mesh, power_up) in power_up_query.iter_mut() {
+        mesh.0 = food_meshes.power_up(power_up.kind, shape_coded);
+    }
+}
+
+fn record_base_font_sizes(
+    mut commands: Commands,
+    text_query: Query<(Entity, &Text), (With<Node>, Without<BaseFontSize>)>,
+) {
+    for (entity, text) in text_query.iter() {
+        let sizes = text
+            .sections
+            .iter()
+            .map(|section| section.style.font_size)
+            .collect();
+        commands.entity(entity).insert(BaseFontSize(sizes));
+    }
+}
+
+fn apply_text_scale(
+    settings: Res<Settings>,
+    mut text_query: Query<(&mut Text, Ref<BaseFontSize>)>,
+) {
+    for (mut text, base) in text_query.iter_mut() {
+        if !settings.is_changed() && !base.is_added() {
+            continue;
+        }
+        for (section, base_size) in text.sections.iter_mut().zip(base.0.iter()) {
+            section.style.font_size = base_size * settings.text_scale;
+        }
+    }
+}
+
+fn apply_game_speed(settings: Res<Settings>, mut time: ResMut<Time<Virtual>>) {
+    if settings.is_changed() {
+        time.set_relative_speed(settings.game_speed);
+    }
+}
@@ -0,0 +1,69 @@
+use bevy::{
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+use rand::prelude::*;
+
+use crate::{snake::GameRules, theme::ThemeMaterials};
+
+pub struct ArenaPlugin;
+
+impl Plugin for ArenaPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameRules>()
+            .init_resource::<Arena>()
+            .add_systems(Startup, setup_arena_floor)
+            .add_systems(Update, draw_arena_border);
+    }
+}
+
+/// Playfield centered on the origin, in world units.
+#[derive(Resource)]
+pub struct Arena {
+    pub size: Vec2,
+}
+
+impl FromWorld for Arena {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            size: world.resource::<GameRules>().arena_size,
+        }
+    }
+}
+
+impl Arena {
+    pub fn half_size(&self) -> Vec2 {
+        self.size / 2.0
+    }
+
+    /// Random point at least `margin` away from every edge.
+    pub fn random_point(&self, margin: f32) -> Vec2 {
+        let mut rng = thread_rng();
+        let half_size = self.half_size() - Vec2::splat(margin);
+        Vec2::new(
+            rng.gen_range(-half_size.x..half_size.x),
+            rng.gen_range(-half_size.y..half_size.y),
+        )
+    }
+}
+
+fn setup_arena_floor(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    theme_materials: Res<ThemeMaterials>,
+    arena: Res<Arena>,
+) {
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: Mesh2dHandle(meshes.add(Rectangle::from_size(arena.size))),
+            material: theme_materials.arena.clone(),
+            transform: Transform::from_xyz(0.0, 0.0, -10.0),
+            ..default()
+        },
+        Name::new("ArenaFloor"),
+    ));
+}
+
+fn draw_arena_border(mut gizmos: Gizmos, arena: Res<Arena>) {
+    gizmos.rect_2d(Vec2::ZERO, 0.0, arena.size, Color::WHITE);
+}
@@ -0,0 +1,140 @@
+//! Reference client for the external bot protocol: plays with one of the
+//! built-in brains, over stdin/stdout or as a TCP server taking one game per
+//! connection. A starting point for bots written outside the game.
+//!
+//! ```text
+//! snake-tournament --bot mine=cmd:snake-bot --bot greedy
+//! snake-bot --listen 127.0.0.1:7878 &
+//! snake-tournament --bot mine=tcp:127.0.0.1:7878 --bot greedy
+//! ```
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::TcpListener,
+    process::ExitCode,
+    thread,
+};
+
+use bevy_snake::sim::{
+    protocol::{ServerMessage, SteerMessage, PROTOCOL_VERSION},
+    BrainKind,
+};
+
+const USAGE: &str = "\
+Usage: snake-bot [options]
+
+Options:
+  --brain <name>           greedy, pathfinding or cautious (default cautious)
+  --listen <host:port>     Serve games over TCP instead of stdin/stdout
+  -h, --help               Show this help";
+
+/// Plays one game: answers every state until the game ends or the stream closes.
+fn play(brain: BrainKind, reader: impl BufRead, mut writer: impl Write) -> io::Result<()> {
+    let mut brain = brain.brain();
+    for line in reader.lines() {
+        let line = line?;
+        let message: ServerMessage = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(error) => {
+                eprintln!("snake-bot: ignoring `{line}`: {error}");
+                continue;
+            }
+        };
+        match message {
+            ServerMessage::Start { version, .. } if version != PROTOCOL_VERSION => {
+                eprintln!(
+                    "snake-bot: game speaks protocol {version}, this bot speaks {PROTOCOL_VERSION}"
+                );
+            }
+            ServerMessage::Start { .. } => {}
+            ServerMessage::State(state) => {
+                let reply = SteerMessage {
+                    tick: state.tick,
+                    target: brain.steer(&state.view()).map(|target| target.to_array()),
+                };
+                serde_json::to_writer(&mut writer, &reply)?;
+                writer.write_all(b"\n")?;
+                writer.flush()?;
+            }
+            ServerMessage::End { .. } => break,
+        }
+    }
+    Ok(())
+}
+
+struct Options {
+    brain: BrainKind,
+    listen: Option<String>,
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        brain: BrainKind::Cautious,
+        listen: None,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("`{arg}` needs a value"));
+        match arg.as_str() {
+            "--brain" => options.brain = value()?.parse()?,
+            "--listen" => options.listen = Some(value()?),
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("unknown option `{other}`")),
+        }
+    }
+    Ok(Some(options))
+}
+
+fn main() -> ExitCode {
+    let Options { brain, listen } = match parse_options(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("snake-bot: {error}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let Some(address) = listen else {
+        let stdin = io::stdin().lock();
+        let stdout = io::stdout().lock();
+        return match play(brain, stdin, stdout) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => {
+                eprintln!("snake-bot: {error}");
+                ExitCode::FAILURE
+            }
+        };
+    };
+
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("snake-bot: can't listen on {address}: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!("snake-bot: {} bot listening on {address}", brain.label());
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                eprintln!("snake-bot: {error}");
+                continue;
+            }
+        };
+        thread::spawn(move || {
+            let _ = stream.set_nodelay(true);
+            let reader = match stream.try_clone() {
+                Ok(reader) => BufReader::new(reader),
+                Err(error) => return eprintln!("snake-bot: {error}"),
+            };
+            if let Err(error) = play(brain, reader, &stream) {
+                eprintln!("snake-bot: {error}");
+            }
+        });
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,213 @@
+//! Plays online over loopback with bot-driven clients and spectators under
+//! simulated latency and packet loss, then reports how prediction and
+//! interpolation held up. Starts its own server unless pointed at one.
+//!
+//! ```text
+//! snake-net-test --clients 3 --spectators 2 --seconds 30 --latency-ms 60 --jitter-ms 20 --loss 0.05
+//! ```
+
+use std::{
+    net::{SocketAddr, ToSocketAddrs},
+    process::ExitCode,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use bevy_snake::{
+    cli::{parse_millis, parse_number, parse_seconds, parse_share},
+    net::{Client, ClientState, NetConditions, Server, ServerConfig},
+    sim::{BrainKind, Steer},
+};
+
+const USAGE: &str = "\
+Usage: snake-net-test [options]
+
+Options:
+  --server <host:port>     Join a running server instead of starting one
+  --clients <n>            Bot-driven clients (default 2)
+  --spectators <n>         Clients that only watch (default 1)
+  --bot <name>             greedy, pathfinding or cautious (default cautious)
+  --seconds <n>            How long to play (default 20)
+  --latency-ms <ms>        Simulated one-way delay on both ends (default 50)
+  --jitter-ms <ms>         Simulated extra random delay on both ends (default 10)
+  --loss <share>           Simulated packet loss on both ends, 0 to 1 (default 0.05)
+  -h, --help               Show this help";
+
+struct Options {
+    server: Option<SocketAddr>,
+    clients: usize,
+    spectators: usize,
+    brain: BrainKind,
+    seconds: f32,
+    conditions: NetConditions,
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        server: None,
+        clients: 2,
+        spectators: 1,
+        brain: BrainKind::Cautious,
+        seconds: 20.0,
+        conditions: NetConditions {
+            latency: Duration::from_millis(50),
+            jitter: Duration::from_millis(10),
+            loss: 0.05,
+        },
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("`{arg}` needs a value"));
+        match arg.as_str() {
+            "--server" => {
+                let address = value()?;
+                options.server = address
+                    .to_socket_addrs()
+                    .ok()
+                    .and_then(|mut addresses| addresses.next())
+                    .map(Some)
+                    .ok_or_else(|| format!("can't resolve `{address}`"))?;
+            }
+            "--clients" => options.clients = parse_number(&value()?)?,
+            "--spectators" => options.spectators = parse_number(&value()?)?,
+            "--bot" => options.brain = value()?.parse()?,
+            "--seconds" => options.seconds = parse_seconds(&value()?)?,
+            "--latency-ms" => options.conditions.latency = parse_millis(&value()?)?,
+            "--jitter-ms" => options.conditions.jitter = parse_millis(&value()?)?,
+            "--loss" => options.conditions.loss = parse_share(&value()?)?,
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("unknown option `{other}`")),
+        }
+    }
+    if options.clients == 0 {
+        return Err("--clients must be at least 1".into());
+    }
+    Ok(Some(options))
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("snake-net-test: {error}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let running = AtomicBool::new(true);
+    thread::scope(|scope| {
+        let address = match options.server {
+            Some(address) => address,
+            None => {
+                let config = ServerConfig {
+                    bind: "127.0.0.1:0".to_string(),
+                    players: options.clients,
+                    spectators: options.spectators,
+                    round_delay: 1.0,
+                    conditions: options.conditions,
+                    ..ServerConfig::default()
+                };
+                let mut server = match Server::bind(config) {
+                    Ok(server) => server,
+                    Err(error) => {
+                        eprintln!("snake-net-test: can't start a server: {error}");
+                        return ExitCode::FAILURE;
+                    }
+                };
+                let address = server.local_addr().expect("bound socket has an address");
+                let running = &running;
+                scope.spawn(move || server.run(running));
+                address
+            }
+        };
+        let code = play(&options, address);
+        running.store(false, Ordering::Relaxed);
+        code
+    })
+}
+
+fn play(options: &Options, server: SocketAddr) -> ExitCode {
+    let mut clients = Vec::new();
+    for index in 0..options.clients + options.spectators {
+        let client = if index < options.clients {
+            Client::connect(server, &format!("Test {}", index + 1), options.conditions)
+        } else {
+            Client::spectate(
+                server,
+                &format!("Watcher {}", index + 1),
+                options.conditions,
+            )
+        };
+        match client {
+            Ok(client) => clients.push((client, options.brain.brain())),
+            Err(error) => {
+                eprintln!("snake-net-test: can't open a client socket: {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let started = Instant::now();
+    let length = Duration::from_secs_f32(options.seconds);
+    while started.elapsed() < length {
+        let now = Instant::now();
+        for (client, brain) in clients.iter_mut() {
+            let steer = client
+                .frame(now)
+                .and_then(|frame| client.view(&frame))
+                .and_then(|view| brain.steer(&view))
+                .map_or(Steer::Hold, Steer::Toward);
+            client.update(now, steer);
+        }
+        thread::sleep(Duration::from_millis(4));
+    }
+
+    println!(
+        "client,state,snapshots,missed,late,round_trip_ms,corrections,mean_correction,max_correction,packets_sent,packets_dropped"
+    );
+    let mut connected = true;
+    for (index, (client, _)) in clients.iter().enumerate() {
+        let state = match &client.state {
+            ClientState::Connecting => "connecting".to_string(),
+            ClientState::Connected { player } => format!("player {player}"),
+            ClientState::Watching => "spectator".to_string(),
+            ClientState::Rejected(reason) => format!("rejected: {reason}"),
+        };
+        connected &= if index < options.clients {
+            client.player().is_some()
+        } else {
+            client.state == ClientState::Watching && client.stats.snapshots > 0
+        };
+        let stats = client.stats;
+        let link = client.link_stats();
+        let mean_correction = if stats.corrections == 0 {
+            0.0
+        } else {
+            stats.correction_total / stats.corrections as f32
+        };
+        println!(
+            "{},{},{},{},{},{:.1},{},{:.2},{:.2},{},{}",
+            index + 1,
+            state,
+            stats.snapshots,
+            stats.snapshots_missed,
+            stats.snapshots_late,
+            stats.round_trip * 1000.0,
+            stats.corrections,
+            mean_correction,
+            stats.correction_max,
+            link.sent,
+            link.dropped,
+        );
+    }
+    if connected {
+        ExitCode::SUCCESS
+    } else {
+        eprintln!("snake-net-test: not every client got a seat, or a spectator saw nothing");
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,103 @@
+//! Dedicated server for online play. Runs the authoritative game headless and
+//! streams snapshots to every connected client over UDP.
+//!
+//! ```text
+//! snake-server --bind 0.0.0.0:5000 --players 4 --bots 2 --record night.replay
+//! ```
+
+use std::{path::PathBuf, process::ExitCode, sync::atomic::AtomicBool};
+
+use bevy_snake::{
+    cli::{parse_millis, parse_number, parse_share},
+    net::{Server, ServerConfig},
+};
+
+const USAGE: &str = "\
+Usage: snake-server [options]
+
+Options:
+  --bind <host:port>         Address to listen on (default 127.0.0.1:5000)
+  --players <n>              Player seats (default 2)
+  --spectators <n>           Spectators allowed at once (default 8)
+  --bots <n>                 Bots added to every round (default 0)
+  --bot <name>               greedy, pathfinding or cautious (default cautious)
+  --difficulty <name>        easy, normal or hard (default normal)
+  --tick-rate <hz>           Simulation ticks per second (default 60)
+  --snapshot-interval <n>    Ticks between snapshots (default 2)
+  --latency-ms <ms>          Simulated delay on every packet sent (default 0)
+  --jitter-ms <ms>           Simulated extra random delay (default 0)
+  --loss <share>             Simulated share of packets dropped, 0 to 1 (default 0)
+  --record <file>            Write every snapshot to a replay file
+  -h, --help                 Show this help";
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Option<ServerConfig>, String> {
+    let mut config = ServerConfig::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("`{arg}` needs a value"));
+        match arg.as_str() {
+            "--bind" => config.bind = value()?,
+            "--players" => config.players = parse_number(&value()?)?,
+            "--spectators" => config.spectators = parse_number(&value()?)?,
+            "--bots" => config.bots = parse_number(&value()?)?,
+            "--bot" => config.bot_brain = value()?.parse()?,
+            "--difficulty" => config.difficulty = value()?.parse()?,
+            "--tick-rate" => config.tick_rate = parse_number(&value()?)?,
+            "--snapshot-interval" => config.snapshot_interval = parse_number(&value()?)?,
+            "--latency-ms" => config.conditions.latency = parse_millis(&value()?)?,
+            "--jitter-ms" => config.conditions.jitter = parse_millis(&value()?)?,
+            "--loss" => config.conditions.loss = parse_share(&value()?)?,
+            "--record" => config.record = Some(PathBuf::from(value()?)),
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("unknown option `{other}`")),
+        }
+    }
+    if config.players == 0 || config.players + config.bots > u8::MAX as usize {
+        return Err("--players must be at least 1, with at most 255 snakes in all".into());
+    }
+    if config.tick_rate == 0 || config.snapshot_interval == 0 {
+        return Err("--tick-rate and --snapshot-interval must be positive".into());
+    }
+    Ok(Some(config))
+}
+
+fn main() -> ExitCode {
+    let config = match parse_options(std::env::args().skip(1)) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("snake-server: {error}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    // Warnings from the server, such as oversized snapshots, go to stderr.
+    bevy::log::tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+
+    let mut server = match Server::bind(config) {
+        Ok(server) => server,
+        Err(error) => {
+            eprintln!("snake-server: can't start: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Ok(address) = server.local_addr() {
+        eprintln!(
+            "snake-server: listening on {address} with {} seats and room for {} spectators",
+            server.config.players, server.config.spectators
+        );
+    }
+    if let Some(path) = &server.config.record {
+        eprintln!("snake-server: recording to {}", path.display());
+    }
+    match server.run(&AtomicBool::new(true)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("snake-server: recording failed: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Plays batches of headless games with a bot and prints score, survival and
+//! death-cause statistics, for comparing tuning changes without hand-playing.
+//!
+//! ```text
+//! snake-sim --games 500 --bot cautious --difficulty hard --format json
+//! ```
+
+use std::{ops::Range, process::ExitCode};
+
+use bevy_snake::{
+    cli::{parse_number, parse_seconds},
+    sim::{run_batch, BatchConfig, Summary},
+};
+
+const USAGE: &str = "\
+Usage: snake-sim [options]
+
+Options:
+  --games <n>              Play seeds 0..n (default 100)
+  --seeds <start>..<end>   Play one game per seed in the range
+  --bot <name>             greedy, pathfinding or cautious (default pathfinding)
+  --difficulty <name>      easy, normal or hard (default normal)
+  --level <name>           Arena size: small, normal or large (default normal)
+  --opponents <n>          Bots of the same kind sharing the arena (default 0)
+  --max-time <seconds>     Simulated seconds before a game times out (default 300)
+  --tick <seconds>         Simulated seconds per tick (default 1/60)
+  --format <csv|json>      Output format (default csv)
+  --per-game               Print one row per game instead of the summary
+  -h, --help               Show this help";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Csv,
+    Json,
+}
+
+struct Options {
+    batch: BatchConfig,
+    format: Format,
+    per_game: bool,
+}
+
+fn parse_seeds(range: &str) -> Result<Range<u64>, String> {
+    let (start, end) = range
+        .split_once("..")
+        .ok_or_else(|| format!("seed range `{range}` should look like 0..100"))?;
+    let start = parse_number(start)?;
+    let end = parse_number(end)?;
+    if end <= start {
+        return Err(format!("seed range `{range}` is empty"));
+    }
+    Ok(start..end)
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        batch: BatchConfig::default(),
+        format: Format::Csv,
+        per_game: false,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("`{arg}` needs a value"));
+        match arg.as_str() {
+            "--games" => {
+                let games: u64 = parse_number(&value()?)?;
+                if games == 0 {
+                    return Err("--games must be at least 1".to_string());
+                }
+                options.batch.seeds = 0..games;
+            }
+            "--seeds" => options.batch.seeds = parse_seeds(&value()?)?,
+            "--bot" => options.batch.brain = value()?.parse()?,
+            "--difficulty" => options.batch.difficulty = value()?.parse()?,
+            "--level" => options.batch.level = value()?.parse()?,
+            "--opponents" => options.batch.opponents = parse_number(&value()?)?,
+            "--max-time" => options.batch.max_time = parse_seconds(&value()?)?,
+            "--tick" => options.batch.tick = parse_seconds(&value()?)?,
+            "--format" => {
+                options.format = match value()?.as_str() {
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    other => return Err(format!("unknown format `{other}`")),
+                }
+            }
+            "--per-game" => options.per_game = true,
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("unknown option `{other}`")),
+        }
+    }
+    Ok(Some(options))
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("snake-sim: {error}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let results = run_batch(&options.batch);
+    match (options.format, options.per_game) {
+        (Format::Csv, false) => {
+            println!("{}", Summary::CSV_HEADER);
+            println!("{}", Summary::new(&options.batch, &results).csv_row());
+        }
+        (Format::Csv, true) => {
+            println!("seed,score,survival_time,outcome");
+            for result in results.iter() {
+                println!(
+                    "{},{},{:.2},{}",
+                    result.seed,
+                    result.score,
+                    result.survival_time,
+                    result.outcome.label()
+                );
+            }
+        }
+        (Format::Json, false) => {
+            let summary = Summary::new(&options.batch, &results);
+            println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+        }
+        (Format::Json, true) => {
+            println!("{}", serde_json::to_string_pretty(&results).unwrap());
+        }
+    }
+    ExitCode::SUCCESS
+}
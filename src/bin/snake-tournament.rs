@@ -0,0 +1,144 @@
+//! Runs a round robin between bots, built-in or external, and prints the standings.
+//!
+//! ```text
+//! snake-tournament --bot mine=cmd:./target/release/snake-bot --bot cautious --games 20
+//! ```
+
+use std::process::ExitCode;
+
+use bevy_snake::{
+    cli::{parse_millis, parse_number, parse_seconds},
+    sim::{run_tournament, Entrant, MatchResult, Standing, TournamentConfig},
+};
+
+const USAGE: &str = "\
+Usage: snake-tournament --bot <bot> --bot <bot> [options]
+
+Bots:
+  greedy, pathfinding, cautious   A built-in bot
+  cmd:<program> [args...]         Started per game, spoken to over stdin/stdout
+  tcp:<host>:<port>               A bot server, connected to per game
+  <name>=<bot>                    Any of the above under its own name
+
+Options:
+  --bot <bot>              Add an entrant; at least two are needed
+  --games <n>              Games per pairing (default 10)
+  --difficulty <name>      easy, normal or hard (default normal)
+  --max-time <seconds>     Simulated seconds before the higher score wins (default 120)
+  --tick <seconds>         Simulated seconds per tick (default 1/60)
+  --deadline-ms <ms>       Time an external bot gets per tick (default 20)
+  --format <csv|json>      Output format (default csv)
+  --per-game               Print one row per game instead of the standings
+  -h, --help               Show this help";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Csv,
+    Json,
+}
+
+struct Options {
+    tournament: TournamentConfig,
+    entrants: Vec<Entrant>,
+    format: Format,
+    per_game: bool,
+}
+
+fn parse_entrant(arg: &str) -> Result<Entrant, String> {
+    // Names can't start with a spec prefix, so `cmd:run --x=1` stays one spec.
+    let (name, spec) = match arg.split_once('=') {
+        Some((name, spec)) if !name.contains(':') => (name.to_string(), spec),
+        _ => (arg.to_string(), arg),
+    };
+    Ok(Entrant {
+        name,
+        spec: spec.parse()?,
+    })
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        tournament: TournamentConfig::default(),
+        entrants: Vec::new(),
+        format: Format::Csv,
+        per_game: false,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("`{arg}` needs a value"));
+        match arg.as_str() {
+            "--bot" => options.entrants.push(parse_entrant(&value()?)?),
+            "--games" => options.tournament.games = parse_number(&value()?)?,
+            "--difficulty" => options.tournament.difficulty = value()?.parse()?,
+            "--max-time" => options.tournament.max_time = parse_seconds(&value()?)?,
+            "--tick" => options.tournament.tick = parse_seconds(&value()?)?,
+            "--deadline-ms" => options.tournament.deadline = parse_millis(&value()?)?,
+            "--format" => {
+                options.format = match value()?.as_str() {
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    other => return Err(format!("unknown format `{other}`")),
+                }
+            }
+            "--per-game" => options.per_game = true,
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("unknown option `{other}`")),
+        }
+    }
+    if options.entrants.len() < 2 {
+        return Err("a tournament needs at least two --bot entrants".into());
+    }
+    let mut names: Vec<&str> = options.entrants.iter().map(|e| e.name.as_str()).collect();
+    names.sort_unstable();
+    if let Some(pair) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(format!(
+            "two entrants are called `{}`; name them with <name>=<bot>",
+            pair[0]
+        ));
+    }
+    Ok(Some(options))
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("snake-tournament: {error}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = run_tournament(&options.tournament, &options.entrants);
+    for game in result.games.iter() {
+        for error in game.errors.iter() {
+            eprintln!("snake-tournament: seed {}: {error}", game.seed);
+        }
+    }
+    match (options.format, options.per_game) {
+        (Format::Csv, false) => {
+            println!("{}", Standing::CSV_HEADER);
+            for standing in result.standings.iter() {
+                println!("{}", standing.csv_row());
+            }
+        }
+        (Format::Csv, true) => {
+            println!("{}", MatchResult::CSV_HEADER);
+            for game in result.games.iter() {
+                println!("{}", game.csv_row());
+            }
+        }
+        (Format::Json, false) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&result.standings).unwrap()
+            );
+        }
+        (Format::Json, true) => {
+            println!("{}", serde_json::to_string_pretty(&result.games).unwrap());
+        }
+    }
+    ExitCode::SUCCESS
+}
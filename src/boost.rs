@@ -0,0 +1,177 @@
+use bevy::{
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    utils::HashSet,
+};
+
+use crate::{
+    accessibility::FoodMeshes,
+    event::SnakeCollideEvent,
+    game_state::GameState,
+    settings::Settings,
+    snake::{GameRules, InputSource, Player, SnakeHead, SnakeSegment, SnakeSegments},
+    theme::ThemeMaterials,
+};
+
+pub struct BoostPlugin;
+
+impl Plugin for BoostPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BoostConfig::default()).add_systems(
+            Update,
+            (update_boost, drain_tail_segments, eat_pellets).chain(),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct BoostConfig {
+    /// Factor applied to `SnakeSpeed` while boosting.
+    pub speed_multiplier: f32,
+    /// Seconds between each tail segment consumed while boosting.
+    pub drain_interval: f32,
+    /// Boosting stops once the snake, head included, is this short.
+    pub min_length: usize,
+    pub pellet_radius: f32,
+    /// Pellets a snake has to eat to win back one segment. Pellets never
+    /// score, feed the combo or speed the snake up.
+    pub pellets_per_segment: u32,
+}
+
+impl Default for BoostConfig {
+    fn default() -> Self {
+        Self {
+            speed_multiplier: 1.8,
+            drain_interval: 0.25,
+            min_length: 3,
+            pellet_radius: 3.0,
+            pellets_per_segment: 2,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Boost {
+    pub active: bool,
+    drain_timer: Timer,
+    /// Pellets eaten toward the next segment.
+    pellets_eaten: u32,
+}
+
+impl Boost {
+    pub fn new(config: &BoostConfig) -> Self {
+        Self {
+            active: false,
+            drain_timer: Timer::from_seconds(config.drain_interval, TimerMode::Repeating),
+            pellets_eaten: 0,
+        }
+    }
+
+    pub fn speed_scale(&self, config: &BoostConfig) -> f32 {
+        if self.active {
+            config.speed_multiplier
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Small food dropped behind a boosting snake. Eating enough of them regrows
+/// a segment, so boosting is never free.
+#[derive(Component)]
+pub struct Pellet;
+
+fn update_boost(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut snake_query: Query<(&SnakeSegments, &InputSource, &mut Boost)>,
+    config: Res<BoostConfig>,
+    state: Res<State<GameState>>,
+) {
+    for (snake_segments, controls, mut boost) in snake_query.iter_mut() {
+        let wants_boost = match controls {
+            InputSource::Mouse => {
+                mouse_input.pressed(MouseButton::Left) || keyboard_input.pressed(KeyCode::Space)
+            }
+            InputSource::Keyboard(layout) => keyboard_input.pressed(layout.boost),
+            InputSource::Bot(_) => false,
+        };
+        let active = state.get() == &GameState::InGame
+            && wants_boost
+            && snake_segments.0.len() > config.min_length;
+
+        if active && !boost.active {
+            boost.drain_timer.reset();
+        }
+        boost.active = active;
+    }
+}
+
+fn drain_tail_segments(
+    mut commands: Commands,
+    food_meshes: Res<FoodMeshes>,
+    settings: Res<Settings>,
+    theme_materials: Res<ThemeMaterials>,
+    mut snake_query: Query<(&mut SnakeSegments, &mut Boost), With<Player>>,
+    segment_query: Query<&Transform, With<SnakeSegment>>,
+    config: Res<BoostConfig>,
+    time: Res<Time>,
+) {
+    for (mut snake_segments, mut boost) in snake_query.iter_mut() {
+        if !boost.active || !boost.drain_timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+        if snake_segments.0.len() <= config.min_length {
+            continue;
+        }
+        let Some(tail_segment) = snake_segments.0.pop() else {
+            continue;
+        };
+
+        if let Ok(segment_transform) = segment_query.get(tail_segment) {
+            commands
+                .spawn(MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(food_meshes.pellet(settings.shape_coded_food)),
+                    material: theme_materials.pellet.clone(),
+                    transform: Transform::from_translation(segment_transform.translation),
+                    ..default()
+                })
+                .insert(Pellet)
+                .insert(Name::new("Pellet"));
+        }
+        commands.entity(tail_segment).despawn();
+    }
+}
+
+fn eat_pellets(
+    mut commands: Commands,
+    mut snake_collide_event: EventWriter<SnakeCollideEvent>,
+    mut snake_query: Query<(Entity, &Transform, &mut Boost), With<SnakeHead>>,
+    pellet_query: Query<(Entity, &Transform), With<Pellet>>,
+    config: Res<BoostConfig>,
+    rules: Res<GameRules>,
+    state: Res<State<GameState>>,
+) {
+    if state.get() != &GameState::InGame {
+        return;
+    }
+    let mut eaten = HashSet::new();
+    for (snake, snake_transform, mut boost) in snake_query.iter_mut() {
+        let snake_position = snake_transform.translation.truncate();
+        for (pellet, pellet_transform) in pellet_query.iter() {
+            let distance = snake_position.distance(pellet_transform.translation.truncate());
+            if distance >= rules.snake_radius + config.pellet_radius || !eaten.insert(pellet) {
+                continue;
+            }
+            commands.entity(pellet).despawn();
+            boost.pellets_eaten += 1;
+            if boost.pellets_eaten >= config.pellets_per_segment {
+                boost.pellets_eaten = 0;
+                snake_collide_event.send(SnakeCollideEvent {
+                    snake,
+                    speed_up: false,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use std::{
+    sync::{
+        mpsc::{self, Receiver, SyncSender},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use bevy_snake::sim::{BotSpec, WorldView};
+
+use crate::{
+    arena::Arena,
+    coin::Coin,
+    game_state::GameState,
+    snake::{GameRules, Player, SnakeHead, SnakeSegments, SnakeSpeed, SteeringTarget},
+};
+
+pub use bevy_snake::sim::{BrainKind, SnakeBrain};
+
+pub struct BotPlugin;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ExternalBot::from_args())
+            .add_systems(Update, think.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// A bot outside the game, given as `--external-bot <spec>` on the command line
+/// (`cmd:<program>` or `tcp:<host>:<port>`). It takes the first bot seat every round.
+#[derive(Resource, Default)]
+pub struct ExternalBot {
+    pub spec: Option<BotSpec>,
+    /// How long the bot gets to answer each state. The game doesn't wait for it:
+    /// the snake keeps its last target until an answer arrives.
+    pub deadline: Duration,
+}
+
+impl ExternalBot {
+    fn from_args() -> ExternalBot {
+        let mut args = std::env::args().skip_while(|arg| arg != "--external-bot");
+        let spec = args.nth(1).and_then(|spec| {
+            spec.parse()
+                .map_err(|error| warn!("Ignoring --external-bot: {error}"))
+                .ok()
+        });
+        ExternalBot {
+            spec,
+            deadline: Duration::from_millis(20),
+        }
+    }
+
+    /// A fresh connection to the external bot if it plays as `player`. The bot
+    /// is started and spoken to on its own thread, so neither a slow start nor a
+    /// slow answer holds up a frame. If it can't be started, the seat plays with
+    /// `fallback` instead.
+    pub fn brain(&self, player: Player, fallback: BrainKind) -> Option<Box<dyn SnakeBrain>> {
+        if player != Player(Player::FIRST_BOT) {
+            return None;
+        }
+        let spec = self.spec.clone()?;
+        if let BotSpec::BuiltIn(kind) = spec {
+            return Some(kind.brain());
+        }
+        let deadline = self.deadline;
+        Some(Box::new(BackgroundBrain::start(move || {
+            spec.brain(deadline).unwrap_or_else(|error| {
+                warn!("Couldn't start external bot {spec}: {error}");
+                fallback.brain()
+            })
+        })))
+    }
+}
+
+/// A brain that thinks on a worker thread. Each frame hands it the newest view
+/// and picks up whatever target it has finished with, keeping the last one
+/// meanwhile.
+struct BackgroundBrain {
+    views: SyncSender<WorldView>,
+    // Only used through `&mut self`; the lock just makes the brain `Sync`.
+    targets: Mutex<Receiver<Option<Vec2>>>,
+    target: Option<Vec2>,
+}
+
+impl BackgroundBrain {
+    fn start(brain: impl FnOnce() -> Box<dyn SnakeBrain> + Send + 'static) -> BackgroundBrain {
+        // Room for one view: while the brain is busy, newer frames are skipped
+        // rather than queued up behind it.
+        let (views, view_receiver) = mpsc::sync_channel::<WorldView>(1);
+        let (target_sender, targets) = mpsc::channel();
+        thread::spawn(move || {
+            let mut brain = brain();
+            // Ends when the snake, and with it the sender, is gone.
+            for view in view_receiver {
+                if target_sender.send(brain.steer(&view)).is_err() {
+                    break;
+                }
+            }
+        });
+        BackgroundBrain {
+            views,
+            targets: Mutex::new(targets),
+            target: None,
+        }
+    }
+}
+
+impl SnakeBrain for BackgroundBrain {
+    fn steer(&mut self, view: &WorldView) -> Option<Vec2> {
+        let targets = self
+            .targets
+            .get_mut()
+            .unwrap_or_else(|error| error.into_inner());
+        while let Ok(target) = targets.try_recv() {
+            self.target = target;
+        }
+        // A full channel means the brain is still on an earlier view.
+        let _ = self.views.try_send(view.clone());
+        self.target
+    }
+}
+
+/// The brain steering a bot snake.
+#[derive(Component)]
+pub struct Bot(pub Box<dyn SnakeBrain>);
+
+/// Asks each bot's brain for a steering target from a snapshot of the arena.
+fn think(
+    mut bot_query: Query<(Entity, &mut Bot, &mut SteeringTarget)>,
+    snake_query: Query<(Entity, &SnakeSegments, &SnakeSpeed, &Transform), With<SnakeHead>>,
+    segment_query: Query<&Transform>,
+    coin_query: Query<&Transform, With<Coin>>,
+    arena: Res<Arena>,
+    rules: Res<GameRules>,
+) {
+    let coins: Vec<Vec2> = coin_query
+        .iter()
+        .map(|transform| transform.translation.truncate())
+        .collect();
+    let bodies: Vec<(Entity, Vec<Vec2>)> = snake_query
+        .iter()
+        .map(|(snake, segments, ..)| {
+            let body = segments
+                .0
+                .iter()
+                .filter_map(|segment| segment_query.get(*segment).ok())
+                .map(|transform| transform.translation.truncate())
+                .collect();
+            (snake, body)
+        })
+        .collect();
+
+    for (snake, mut bot, mut target) in bot_query.iter_mut() {
+        let Ok((_, _, speed, head_transform)) = snake_query.get(snake) else {
+            continue;
+        };
+        let Some((_, own)) = bodies.iter().find(|(other, _)| *other == snake) else {
+            continue;
+        };
+        let view = WorldView {
+            head: head_transform.translation.truncate(),
+            heading: (head_transform.rotation * Vec3::X).truncate(),
+            speed: speed.0,
+            snake_radius: rules.snake_radius,
+            own_body: own.iter().skip(1).copied().collect(),
+            others: bodies
+                .iter()
+                .filter(|(other, _)| *other != snake)
+                .flat_map(|(_, body)| body.iter().copied())
+                .collect(),
+            coins: coins.clone(),
+            arena_half_size: arena.half_size(),
+        };
+        target.0 = bot.0.steer(&view);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    /// Takes its time, like an external bot near its deadline.
+    struct SlowBrain;
+
+    impl SnakeBrain for SlowBrain {
+        fn steer(&mut self, view: &WorldView) -> Option<Vec2> {
+            thread::sleep(Duration::from_millis(50));
+            Some(view.head + Vec2::X)
+        }
+    }
+
+    #[test]
+    fn background_brain_never_blocks_and_keeps_its_last_target() {
+        let mut brain = BackgroundBrain::start(|| Box::new(SlowBrain));
+        let view = WorldView::default();
+
+        let started = Instant::now();
+        assert_eq!(brain.steer(&view), None);
+        assert!(started.elapsed() < Duration::from_millis(20));
+
+        let give_up = Instant::now() + Duration::from_secs(5);
+        let mut target = None;
+        while target.is_none() && Instant::now() < give_up {
+            thread::sleep(Duration::from_millis(10));
+            target = brain.steer(&view);
+        }
+        assert_eq!(target, Some(Vec2::X));
+        assert_eq!(brain.steer(&view), Some(Vec2::X));
+    }
+}
@@ -0,0 +1,224 @@
+use bevy::{
+    prelude::*,
+    render::camera::{ScalingMode, Viewport},
+    transform::TransformSystem,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    arena::Arena,
+    juice::{JuiceConfig, Trauma},
+    settings::Settings,
+    snake::{Player, SnakeHead, SnakeSegments, SnakeSpeed},
+};
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CameraConfig::default())
+            // Shows in the letterbox bars; the arena floor carries the theme background.
+            .insert_resource(ClearColor(Color::BLACK))
+            .add_systems(Startup, setup_camera2d)
+            .add_systems(
+                PostUpdate,
+                update_camera.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Centered on the arena and zoomed to show all of it.
+    Fixed,
+    /// Smoothly tracks the head, leading it in the direction of travel.
+    Follow,
+    /// Like `Follow`, zooming out as the snake grows.
+    FollowZoom,
+}
+
+impl CameraMode {
+    const ALL: [CameraMode; 3] = [
+        CameraMode::Fixed,
+        CameraMode::Follow,
+        CameraMode::FollowZoom,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CameraMode::Fixed => "Fixed",
+            CameraMode::Follow => "Follow",
+            CameraMode::FollowZoom => "Follow + Zoom",
+        }
+    }
+
+    pub fn cycle(&self, step: i32) -> CameraMode {
+        let index = Self::ALL.iter().position(|mode| mode == self).unwrap_or(0) as i32;
+        Self::ALL[(index + step).rem_euclid(Self::ALL.len() as i32) as usize]
+    }
+}
+
+#[derive(Resource)]
+pub struct CameraConfig {
+    /// World units visible at zoom 1, whatever the window size. The view is
+    /// letterboxed to keep this aspect ratio.
+    pub view_size: Vec2,
+    /// How quickly the camera catches up with its target, per second.
+    pub follow_smoothing: f32,
+    /// Seconds of head travel the camera looks ahead by.
+    pub look_ahead: f32,
+    /// Extra zoom-out per body segment in `FollowZoom` mode.
+    pub zoom_per_segment: f32,
+    pub max_zoom: f32,
+    pub zoom_smoothing: f32,
+    /// World units kept around the heads when several snakes share the view.
+    pub frame_margin: Vec2,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        Self {
+            view_size: Vec2::new(1280.0, 720.0),
+            follow_smoothing: 4.0,
+            look_ahead: 0.4,
+            zoom_per_segment: 0.01,
+            max_zoom: 2.0,
+            zoom_smoothing: 2.0,
+            frame_margin: Vec2::new(400.0, 300.0),
+        }
+    }
+}
+
+/// Where the camera is looking before screen shake is added on top.
+#[derive(Component, Default)]
+struct CameraFocus(Vec2);
+
+fn setup_camera2d(mut commands: Commands, config: Res<CameraConfig>) {
+    let mut camera = Camera2dBundle {
+        transform: Transform {
+            translation: Vec3::new(0.0, 0.0, 0.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    camera.projection.scaling_mode = ScalingMode::Fixed {
+        width: config.view_size.x,
+        height: config.view_size.y,
+    };
+    commands.spawn((camera, CameraFocus::default()));
+}
+
+/// Largest viewport with the aspect ratio of `view_size` that fits in the
+/// window, centered so the leftover space forms bars on two sides.
+fn letterbox(window_size: UVec2, view_size: Vec2) -> Viewport {
+    let window_size = window_size.as_vec2();
+    let fit = (window_size / view_size).min_element();
+    let size = (view_size * fit).round().max(Vec2::ONE);
+    Viewport {
+        physical_position: ((window_size - size) / 2.0).max(Vec2::ZERO).as_uvec2(),
+        physical_size: size.as_uvec2(),
+        ..default()
+    }
+}
+
+fn update_camera(
+    settings: Res<Settings>,
+    config: Res<CameraConfig>,
+    arena: Res<Arena>,
+    head_query: Query<
+        (&Transform, &Player, &SnakeSpeed, &SnakeSegments),
+        (With<SnakeHead>, Without<Camera>),
+    >,
+    mut camera_query: Query<(
+        &mut Camera,
+        &mut Transform,
+        &mut OrthographicProjection,
+        &mut CameraFocus,
+    )>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    trauma: Res<Trauma>,
+    juice_config: Res<JuiceConfig>,
+    time: Res<Time>,
+    real_time: Res<Time<Real>>,
+) {
+    let Ok((mut camera, mut camera_transform, mut projection, mut focus)) =
+        camera_query.get_single_mut()
+    else {
+        return;
+    };
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let window_size = UVec2::new(window.physical_width(), window.physical_height());
+    // Minimized windows report a zero size.
+    if window_size.min_element() == 0 {
+        return;
+    }
+    let viewport = letterbox(window_size, config.view_size);
+    let viewport_unchanged = camera.viewport.as_ref().is_some_and(|current| {
+        current.physical_position == viewport.physical_position
+            && current.physical_size == viewport.physical_size
+    });
+    if !viewport_unchanged {
+        camera.viewport = Some(viewport);
+    }
+
+    let arena_fit = (arena.size / config.view_size).max_element();
+    // Follow the players' snakes, or the bots once no player is left.
+    let mut heads: Vec<_> = head_query
+        .iter()
+        .filter(|(_, player, ..)| !player.is_bot())
+        .collect();
+    if heads.is_empty() {
+        heads = head_query.iter().collect();
+    }
+    let (target, target_scale) = match (settings.camera_mode, heads.len()) {
+        (CameraMode::Fixed, _) | (_, 0) => (Vec2::ZERO, arena_fit),
+        (mode, 1) => {
+            let (head_transform, _, snake_speed, segments) = heads[0];
+            let heading = (head_transform.rotation * Vec3::X).truncate();
+            let look_ahead = heading * snake_speed.0 * config.look_ahead;
+            let scale = if mode == CameraMode::FollowZoom {
+                (1.0 + segments.0.len() as f32 * config.zoom_per_segment).min(config.max_zoom)
+            } else {
+                1.0
+            };
+            (head_transform.translation.truncate() + look_ahead, scale)
+        }
+        // With several snakes alive, frame all the heads at once.
+        _ => {
+            let positions = heads
+                .iter()
+                .map(|(transform, ..)| transform.translation.truncate());
+            let min = positions.clone().fold(Vec2::MAX, Vec2::min);
+            let max = positions.fold(Vec2::MIN, Vec2::max);
+            let scale = ((max - min + config.frame_margin) / config.view_size)
+                .max_element()
+                .clamp(1.0, arena_fit.max(1.0));
+            ((min + max) / 2.0, scale)
+        }
+    };
+
+    let delta = time.delta_seconds();
+    let zoom_blend = 1.0 - (-config.zoom_smoothing * delta).exp();
+    projection.scale += (target_scale - projection.scale) * zoom_blend;
+
+    let follow_blend = 1.0 - (-config.follow_smoothing * delta).exp();
+    let mut position = focus.0.lerp(target, follow_blend);
+
+    // Keep the view inside the arena, or centered on it when the view is larger.
+    let half_view = config.view_size / 2.0 * projection.scale;
+    let limit = (arena.half_size() - half_view).max(Vec2::ZERO);
+    position = position.clamp(-limit, limit);
+    focus.0 = position;
+
+    // Shake runs on the real clock so it keeps going through hit-stop.
+    let (offset, angle) = trauma.shake(
+        &juice_config,
+        settings.shake_intensity,
+        real_time.elapsed_seconds(),
+    );
+    camera_transform.translation =
+        (position + offset * projection.scale).extend(camera_transform.translation.z);
+    camera_transform.rotation = Quat::from_rotation_z(angle);
+}
@@ -0,0 +1,84 @@
+//! Value parsing shared by the command line tools and the game's own flags.
+//! Errors are plain messages, ready to print above a tool's usage text.
+
+use std::{str::FromStr, time::Duration};
+
+/// Parses a number of any type, ignoring surrounding spaces.
+pub fn parse_number<T: FromStr>(value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("`{value}` is not a valid number"))
+}
+
+/// Parses a delay in milliseconds, fractions allowed. Negative, NaN and
+/// overlong values are errors rather than a panic in [`Duration`].
+pub fn parse_millis(value: &str) -> Result<Duration, String> {
+    let millis: f64 = parse_number(value)?;
+    if millis < 0.0 {
+        return Err(format!("`{value}` milliseconds can't be negative"));
+    }
+    Duration::try_from_secs_f64(millis / 1000.0)
+        .map_err(|_| format!("`{value}` is not a valid number of milliseconds"))
+}
+
+/// Parses a positive number of seconds, such as a tick length or a time limit.
+/// Zero, negative, NaN and infinite values are errors, as are values too long
+/// for a [`Duration`].
+pub fn parse_seconds(value: &str) -> Result<f32, String> {
+    let seconds: f32 = parse_number(value)?;
+    if seconds.is_nan() || seconds <= 0.0 {
+        return Err(format!("`{value}` seconds must be positive"));
+    }
+    Duration::try_from_secs_f32(seconds)
+        .map_err(|_| format!("`{value}` is not a valid number of seconds"))?;
+    Ok(seconds)
+}
+
+/// Parses a share from 0 to 1, such as a packet loss rate.
+pub fn parse_share(value: &str) -> Result<f32, String> {
+    let share: f32 = parse_number(value)?;
+    if !(0.0..=1.0).contains(&share) {
+        return Err(format!("`{value}` should be between 0 and 1"));
+    }
+    Ok(share)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_trimmed() {
+        assert_eq!(parse_number::<u32>(" 42 "), Ok(42));
+        assert!(parse_number::<u32>("-1").is_err());
+        assert!(parse_number::<f32>("fast").is_err());
+    }
+
+    #[test]
+    fn millis_reject_what_duration_would_panic_on() {
+        assert_eq!(parse_millis("2.5"), Ok(Duration::from_micros(2500)));
+        assert_eq!(parse_millis("0"), Ok(Duration::ZERO));
+        for bad in ["-5", "NaN", "inf", "1e300", "soon"] {
+            assert!(parse_millis(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn seconds_must_be_positive_and_finite() {
+        assert_eq!(parse_seconds("0.5"), Ok(0.5));
+        assert_eq!(parse_seconds(" 120 "), Ok(120.0));
+        for bad in ["0", "-1", "NaN", "inf", "1e30", "later"] {
+            assert!(parse_seconds(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn shares_stay_between_zero_and_one() {
+        assert_eq!(parse_share("0.05"), Ok(0.05));
+        assert_eq!(parse_share("1"), Ok(1.0));
+        for bad in ["-0.1", "1.5", "NaN", "inf"] {
+            assert!(parse_share(bad).is_err(), "{bad}");
+        }
+    }
+}
@@ -1,52 +1,51 @@
+use crate::{
+    accessibility::FoodMeshes, arena::Arena, game_state::GameState, settings::Settings,
+    snake::GameRules, theme::ThemeMaterials,
+};
 use bevy::{
     prelude::*,
     sprite::{MaterialMesh2dBundle, Mesh2dHandle},
 };
-use rand::prelude::*;
-
-use crate::game_state::GameState;
 
 pub struct CoinPlugin;
 
 impl Plugin for CoinPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(CoinTimer(Timer::from_seconds(1.0, TimerMode::Repeating)))
+        app.init_resource::<CoinTimer>()
             .add_systems(Update, spawn_coin);
     }
 }
 
-#[derive(Resource)]
-struct CoinTimer(Timer);
+/// Seconds since the last coin spawned.
+#[derive(Resource, Default)]
+struct CoinTimer(f32);
 
 #[derive(Component)]
 pub struct Coin;
 
 fn spawn_coin(
     mut commands: Commands,
-    mut windows: Query<&mut Window>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
+    arena: Res<Arena>,
+    food_meshes: Res<FoodMeshes>,
+    settings: Res<Settings>,
+    theme_materials: Res<ThemeMaterials>,
     mut coin_timer: ResMut<CoinTimer>,
+    rules: Res<GameRules>,
     time: Res<Time>,
     state: Res<State<GameState>>,
 ) {
     if state.get() != &GameState::InGame {
         return;
     }
-    if coin_timer.0.tick(time.delta()).finished() {
-        let window = windows.single_mut();
-        let window_size = Vec2::new(window.width(), window.height());
-        let offset = 30.0;
-
-        let mut rng = thread_rng();
-
-        let x = rng.gen_range((-window_size.x / 2.0) + offset..(window_size.x / 2.0) - offset);
-        let y = rng.gen_range((-window_size.y / 2.0) + offset..(window_size.y / 2.0) - offset);
+    coin_timer.0 += time.delta_seconds();
+    while coin_timer.0 >= rules.coin_interval {
+        coin_timer.0 -= rules.coin_interval;
+        let Vec2 { x, y } = arena.random_point(rules.coin_margin);
 
         commands
             .spawn(MaterialMesh2dBundle {
-                mesh: Mesh2dHandle(meshes.add(Circle::new(5.0))),
-                material: materials.add(Color::RED),
+                mesh: Mesh2dHandle(food_meshes.coin(settings.shape_coded_food)),
+                material: theme_materials.coin.clone(),
                 transform: Transform::from_xyz(x, y, 0.0),
                 ..default()
             })
@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+
+use crate::{
+    event::{CoinCollectedEvent, GameOverEvent},
+    game_state::{GameMode, GameState, Score},
+    power_up::ActiveEffects,
+    settings::Settings,
+    snake::Player,
+    theme::OwnTextColor,
+};
+
+pub struct ComboPlugin;
+
+impl Plugin for ComboPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ComboConfig::default())
+            .add_systems(Startup, setup_combo_label)
+            .add_systems(
+                Update,
+                (
+                    register_combo_hits,
+                    decay_combo,
+                    reset_combo,
+                    update_score_popups,
+                    combo_text_update_system,
+                ),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct ComboConfig {
+    /// Seconds after a pickup during which the next coin raises the multiplier.
+    pub window: f32,
+    /// Seconds between each one-step drop of the multiplier once the window has run out.
+    pub decay_interval: f32,
+    pub max_multiplier: u32,
+    pub popup_lifetime: f32,
+    /// World units per second the popup floats up, unless motion is reduced.
+    pub popup_rise_speed: f32,
+    pub popup_font_size: f32,
+    pub popup_color: Color,
+    pub label_font_size: f32,
+    pub label_color: Color,
+    /// Pixels from the left edge of the window to the combo label.
+    pub label_left: f32,
+}
+
+impl Default for ComboConfig {
+    fn default() -> Self {
+        Self {
+            window: 1.5,
+            decay_interval: 0.75,
+            max_multiplier: 5,
+            popup_lifetime: 0.8,
+            popup_rise_speed: 40.0,
+            popup_font_size: 18.0,
+            popup_color: Color::YELLOW,
+            label_font_size: 20.0,
+            label_color: Color::WHITE,
+            label_left: 220.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Combo {
+    pub multiplier: u32,
+    window: Timer,
+    decay: Timer,
+}
+
+impl Combo {
+    pub fn new(config: &ComboConfig) -> Self {
+        let mut window = Timer::from_seconds(config.window, TimerMode::Once);
+        window.tick(window.duration());
+        Self {
+            multiplier: 1,
+            window,
+            decay: Timer::from_seconds(config.decay_interval, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Component)]
+struct ComboText;
+
+#[derive(Component)]
+struct ScorePopup {
+    timer: Timer,
+}
+
+fn register_combo_hits(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut coin_collected_event: EventReader<CoinCollectedEvent>,
+    mut snake_query: Query<(&mut Combo, &mut Score, &ActiveEffects)>,
+    config: Res<ComboConfig>,
+    state: Res<State<GameState>>,
+) {
+    if state.get() != &GameState::InGame {
+        return;
+    }
+    for event in coin_collected_event.read() {
+        let Ok((mut combo, mut score, effects)) = snake_query.get_mut(event.snake) else {
+            continue;
+        };
+        if !combo.window.finished() {
+            combo.multiplier = (combo.multiplier + 1).min(config.max_multiplier);
+        }
+        combo.window.reset();
+        combo.decay.reset();
+
+        let points = combo.multiplier * effects.score_multiplier();
+        score.0 += points;
+
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("+{points}"),
+                    TextStyle {
+                        font: asset_server.load("font/FiraSans-Bold.ttf"),
+                        font_size: config.popup_font_size,
+                        color: config.popup_color,
+                    },
+                ),
+                transform: Transform::from_translation(event.position.extend(1.0)),
+                ..default()
+            },
+            ScorePopup {
+                timer: Timer::from_seconds(config.popup_lifetime, TimerMode::Once),
+            },
+            Name::new("ScorePopup"),
+        ));
+    }
+}
+
+fn decay_combo(mut combo_query: Query<&mut Combo>, time: Res<Time>, state: Res<State<GameState>>) {
+    if state.get() != &GameState::InGame {
+        return;
+    }
+    for mut combo in combo_query.iter_mut() {
+        combo.window.tick(time.delta());
+        if combo.window.finished() && combo.multiplier > 1 {
+            combo.decay.tick(time.delta());
+            if combo.decay.just_finished() {
+                combo.multiplier -= 1;
+            }
+        }
+    }
+}
+
+fn reset_combo(
+    mut game_over_event: EventReader<GameOverEvent>,
+    mut combo_query: Query<&mut Combo>,
+    config: Res<ComboConfig>,
+) {
+    for _ in game_over_event.read() {
+        for mut combo in combo_query.iter_mut() {
+            *combo = Combo::new(&config);
+        }
+    }
+}
+
+fn update_score_popups(
+    mut commands: Commands,
+    mut popup_query: Query<(Entity, &mut Transform, &mut Text, &mut ScorePopup)>,
+    config: Res<ComboConfig>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform, mut text, mut popup) in popup_query.iter_mut() {
+        popup.timer.tick(time.delta());
+        if popup.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        if !settings.reduced_motion {
+            transform.translation.y += config.popup_rise_speed * time.delta_seconds();
+        }
+        let alpha = 1.0 - popup.timer.fraction();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(alpha);
+        }
+    }
+}
+
+fn combo_text_update_system(
+    mut query: Query<&mut Text, With<ComboText>>,
+    combo_query: Query<(&Player, &Combo)>,
+    settings: Res<Settings>,
+) {
+    let mut combos: Vec<(&Player, &Combo)> = combo_query.iter().collect();
+    combos.sort_by_key(|(player, _)| **player);
+    let value = combos
+        .iter()
+        .map(|(player, combo)| match settings.game_mode {
+            GameMode::Solo => format!("x{}", combo.multiplier),
+            GameMode::Versus => format!("{} x{}", player.label(), combo.multiplier),
+        })
+        .collect::<Vec<_>>()
+        .join("  ");
+    for mut text in &mut query {
+        text.sections[1].value = value.clone();
+    }
+}
+
+fn setup_combo_label(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<ComboConfig>,
+) {
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new(
+                "Combo: ",
+                TextStyle {
+                    font: asset_server.load("font/FiraSans-Bold.ttf"),
+                    font_size: config.label_font_size,
+                    color: config.label_color,
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font_size: config.label_font_size,
+                color: config.label_color,
+                ..default()
+            }),
+        ])
+        .with_style(Style {
+            left: Val::Px(config.label_left),
+            ..Default::default()
+        }),
+        ComboText,
+        OwnTextColor,
+    ));
+}
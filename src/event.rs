@@ -1,6 +1,10 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashSet};
 
-use crate::{coin::Coin, snake::SnakeHead};
+use crate::{
+    coin::Coin,
+    power_up::PowerUpKind,
+    snake::{GameRules, Player, SnakeHead},
+};
 
 pub struct EventPlugin;
 
@@ -9,38 +13,87 @@ impl Plugin for EventPlugin {
         app.add_event::<SnakeCollideEvent>()
             .add_event::<CoinCollectedEvent>()
             .add_event::<GameOverEvent>()
+            .add_event::<SnakeDiedEvent>()
+            .add_event::<NewRoundEvent>()
+            .add_event::<EffectStartedEvent>()
+            .add_event::<EffectEndedEvent>()
             .add_systems(Update, snake_collide_event_writer);
     }
 }
 
+/// A snake ate something and should grow.
 #[derive(Event, Debug)]
-pub struct SnakeCollideEvent;
+pub struct SnakeCollideEvent {
+    pub snake: Entity,
+    /// Coins speed the snake up as it grows; regrowing from pellets doesn't.
+    pub speed_up: bool,
+}
 
 #[derive(Event, Debug)]
-pub struct CoinCollectedEvent;
+pub struct CoinCollectedEvent {
+    pub position: Vec2,
+    pub snake: Entity,
+}
 
+/// The game is over in solo play, or the round is over in versus play.
 #[derive(Event, Debug)]
 pub struct GameOverEvent;
 
+#[derive(Event, Debug)]
+pub struct SnakeDiedEvent {
+    pub player: Player,
+    pub cause: DeathCause,
+    /// Where each of its segments was, head first, since they are gone by the next frame.
+    pub body: Vec<Vec2>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeathCause {
+    /// The head ran into the snake's own body.
+    OwnBody,
+    /// The head ran into the snake owned by this player.
+    Snake(Player),
+}
+
+/// Replaces all snakes with fresh ones for the current game mode.
+#[derive(Event, Debug)]
+pub struct NewRoundEvent;
+
+#[derive(Event, Debug)]
+pub struct EffectStartedEvent(pub PowerUpKind);
+
+#[derive(Event, Debug)]
+pub struct EffectEndedEvent(pub PowerUpKind);
+
 fn snake_collide_event_writer(
     mut snake_collide_event: EventWriter<SnakeCollideEvent>,
     mut coin_collected_event: EventWriter<CoinCollectedEvent>,
     mut commands: Commands,
-    snake_query: Query<&Transform, With<SnakeHead>>,
+    snake_query: Query<(Entity, &Transform), With<SnakeHead>>,
     circle_query: Query<(&Transform, Entity), With<Coin>>,
+    rules: Res<GameRules>,
 ) {
-    if let Ok(snake_transform) = snake_query.get_single() {
+    // Two heads can reach the same coin in one frame; only the first eats it.
+    let mut eaten = HashSet::new();
+    for (snake, snake_transform) in snake_query.iter() {
         for (circle_transform, circle_entity) in circle_query.iter() {
+            if eaten.contains(&circle_entity) {
+                continue;
+            }
             let snake_position = snake_transform.translation.truncate();
             let circle_position = circle_transform.translation.truncate();
             let distance = snake_position.distance(circle_position);
 
-            let snake_radius = 10.0;
-            let circle_radius = 10.0;
-
-            if distance < snake_radius + circle_radius {
-                snake_collide_event.send(SnakeCollideEvent);
-                coin_collected_event.send(CoinCollectedEvent);
+            if distance < rules.snake_radius + rules.coin_radius {
+                eaten.insert(circle_entity);
+                snake_collide_event.send(SnakeCollideEvent {
+                    snake,
+                    speed_up: true,
+                });
+                coin_collected_event.send(CoinCollectedEvent {
+                    position: circle_position,
+                    snake,
+                });
                 commands.entity(circle_entity).despawn();
             }
         }
@@ -1,56 +1,308 @@
-use bevy::prelude::*;
+use bevy::{audio::Volume, prelude::*, utils::HashMap};
+use rand::prelude::*;
 
-use crate::event::{CoinCollectedEvent, GameOverEvent};
+use crate::{
+    event::{CoinCollectedEvent, GameOverEvent},
+    game_state::GameState,
+    music::{Music, MusicTrack},
+    settings::Settings,
+};
 
 pub struct GameAudioPlugin;
 
 impl Plugin for GameAudioPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(GameOverAudioPlayed(false))
-            .add_systems(Update, (play_coin_audio, play_game_over_audio));
+        app.add_event::<PlaySfx>()
+            .insert_resource(GameOverAudioPlayed(false))
+            .insert_resource(SfxConfig::default())
+            .init_resource::<SfxLibrary>()
+            .add_systems(OnEnter(GameState::Paused), pause_audio)
+            .add_systems(OnExit(GameState::Paused), resume_audio)
+            .add_systems(OnExit(GameState::GameOver), rearm_game_over_audio)
+            .add_systems(
+                Update,
+                (
+                    (play_coin_audio, play_game_over_audio, play_sfx).chain(),
+                    toggle_mute,
+                    apply_sfx_volume,
+                ),
+            );
     }
 }
 
+/// Every one-shot sound the game plays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sfx {
+    Coin,
+    GameOver,
+    Slither,
+    Turn,
+    Grow,
+    NearMiss,
+}
+
+impl Sfx {
+    const ALL: [Sfx; 6] = [
+        Sfx::Coin,
+        Sfx::GameOver,
+        Sfx::Slither,
+        Sfx::Turn,
+        Sfx::Grow,
+        Sfx::NearMiss,
+    ];
+
+    fn path(&self) -> &'static str {
+        match self {
+            Sfx::Coin => "audio/coin.ogg",
+            Sfx::GameOver => "audio/gameover.ogg",
+            Sfx::Slither => "audio/slither.wav",
+            Sfx::Turn => "audio/turn.wav",
+            Sfx::Grow => "audio/grow.wav",
+            Sfx::NearMiss => "audio/near_miss.wav",
+        }
+    }
+
+    /// Most copies of this sound allowed to play at once; extra requests are dropped.
+    fn max_instances(&self) -> usize {
+        match self {
+            Sfx::Coin => 4,
+            Sfx::GameOver => 1,
+            Sfx::Slither => 2,
+            Sfx::Turn => 1,
+            Sfx::Grow => 3,
+            Sfx::NearMiss => 1,
+        }
+    }
+
+    /// Whether each play gets a slightly different pitch, so repeats don't drone.
+    fn varies_pitch(&self) -> bool {
+        matches!(self, Sfx::Coin | Sfx::Slither | Sfx::Grow)
+    }
+}
+
+/// Asks the SFX manager to play a sound.
+#[derive(Event, Debug)]
+pub struct PlaySfx(pub Sfx);
+
+#[derive(Resource)]
+pub struct SfxConfig {
+    /// Largest pitch change either way, as a fraction of normal speed.
+    pub pitch_variation: f32,
+}
+
+impl Default for SfxConfig {
+    fn default() -> Self {
+        Self {
+            pitch_variation: 0.08,
+        }
+    }
+}
+
+/// Handles for every [`Sfx`], loaded once up front.
+#[derive(Resource)]
+struct SfxLibrary(HashMap<Sfx, Handle<AudioSource>>);
+
+impl FromWorld for SfxLibrary {
+    fn from_world(world: &mut World) -> Self {
+        // Headless runs may leave out the asset server; sounds then stay silent.
+        let asset_server = world.get_resource::<AssetServer>();
+        Self(
+            Sfx::ALL
+                .iter()
+                .map(|sound| {
+                    let handle = asset_server
+                        .map(|asset_server| asset_server.load(sound.path()))
+                        .unwrap_or_default();
+                    (*sound, handle)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// A one-shot sound on the SFX volume bus. Despawned when it finishes playing.
 #[derive(Component)]
-pub struct CoinColledtedAudio;
+pub struct SoundEffect(pub Sfx);
 
+/// Audio paused by the pause menu, to resume when it closes.
 #[derive(Component)]
-pub struct GameOverAudio;
+struct PausedByMenu;
 
 #[derive(Resource, Default)]
 pub struct GameOverAudioPlayed(pub bool);
 
 fn play_coin_audio(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
     mut coin_collected_event: EventReader<CoinCollectedEvent>,
+    mut play_sfx: EventWriter<PlaySfx>,
 ) {
     for _ in coin_collected_event.read() {
-        commands
-            .spawn(AudioBundle {
-                source: asset_server.load("audio/coin.ogg"),
-                ..default()
-            })
-            .insert(CoinColledtedAudio);
+        play_sfx.send(PlaySfx(Sfx::Coin));
     }
 }
 
 fn play_game_over_audio(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
     mut game_over_event: EventReader<GameOverEvent>,
     mut audio_played: ResMut<GameOverAudioPlayed>,
+    mut play_sfx: EventWriter<PlaySfx>,
 ) {
-    if !audio_played.0 {
-        for _ in game_over_event.read() {
-            commands
-                .spawn(AudioBundle {
-                    source: asset_server.load("audio/gameover.ogg"),
-                    ..default()
-                })
-                .insert(GameOverAudio);
-            audio_played.0 = true;
-            break;
+    if !audio_played.0 && game_over_event.read().next().is_some() {
+        play_sfx.send(PlaySfx(Sfx::GameOver));
+        audio_played.0 = true;
+    }
+}
+
+/// Spawns requested sounds, skipping any already at their instance cap.
+fn play_sfx(
+    mut commands: Commands,
+    mut play_sfx: EventReader<PlaySfx>,
+    playing_query: Query<&SoundEffect>,
+    library: Res<SfxLibrary>,
+    config: Res<SfxConfig>,
+    settings: Res<Settings>,
+) {
+    let mut playing: HashMap<Sfx, usize> = HashMap::new();
+    for SoundEffect(sound) in playing_query.iter() {
+        *playing.entry(*sound).or_default() += 1;
+    }
+
+    let mut rng = thread_rng();
+    for PlaySfx(sound) in play_sfx.read() {
+        let count = playing.entry(*sound).or_default();
+        if *count >= sound.max_instances() {
+            continue;
+        }
+        *count += 1;
+
+        let variation = config.pitch_variation;
+        let speed = if sound.varies_pitch() && variation > 0.0 {
+            rng.gen_range(1.0 - variation..=1.0 + variation)
+        } else {
+            1.0
+        };
+        commands.spawn((
+            AudioBundle {
+                source: library.0[sound].clone(),
+                settings: PlaybackSettings::DESPAWN
+                    .with_volume(Volume::new(settings.sfx_volume()))
+                    .with_speed(speed),
+            },
+            SoundEffect(*sound),
+            Name::new("SoundEffect"),
+        ));
+    }
+}
+
+fn rearm_game_over_audio(mut audio_played: ResMut<GameOverAudioPlayed>) {
+    audio_played.0 = false;
+}
+
+fn toggle_mute(keyboard_input: Option<Res<ButtonInput<KeyCode>>>, mut settings: ResMut<Settings>) {
+    // Headless runs have no keyboard.
+    let Some(keyboard_input) = keyboard_input else {
+        return;
+    };
+    if keyboard_input.just_pressed(KeyCode::KeyM) {
+        settings.muted = !settings.muted;
+    }
+}
+
+/// Carries volume changes over to sounds that are already playing.
+fn apply_sfx_volume(settings: Res<Settings>, sink_query: Query<&AudioSink, With<SoundEffect>>) {
+    if !settings.is_changed() {
+        return;
+    }
+    for sink in sink_query.iter() {
+        sink.set_volume(settings.sfx_volume());
+    }
+}
+
+/// Pauses everything except the menu music while the pause menu is open.
+fn pause_audio(mut commands: Commands, sink_query: Query<(Entity, &AudioSink, Option<&Music>)>) {
+    for (entity, sink, music) in sink_query.iter() {
+        let menu_music = music.is_some_and(|music| music.track == MusicTrack::Menu);
+        if !menu_music && !sink.is_paused() {
+            sink.pause();
+            commands.entity(entity).insert(PausedByMenu);
+        }
+    }
+}
+
+fn resume_audio(
+    mut commands: Commands,
+    sink_query: Query<(Entity, &AudioSink), With<PausedByMenu>>,
+) {
+    for (entity, sink) in sink_query.iter() {
+        sink.play();
+        commands.entity(entity).remove::<PausedByMenu>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::EventPlugin, movement_audio::MovementAudioPlugin, music::MusicPlugin,
+        snake::GameRules,
+    };
+
+    /// The audio plugins without an asset server or audio output, the way a
+    /// headless run or a machine without a sound card sees them.
+    fn headless_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_state::<GameState>()
+            .insert_resource(Settings::default())
+            .init_resource::<GameRules>()
+            .add_plugins((
+                EventPlugin,
+                GameAudioPlugin,
+                MusicPlugin,
+                MovementAudioPlugin,
+            ));
+        for _ in 0..3 {
+            app.update();
+        }
+        app
+    }
+
+    fn playing(app: &mut App, sound: Sfx) -> usize {
+        app.world
+            .query::<&SoundEffect>()
+            .iter(&app.world)
+            .filter(|effect| effect.0 == sound)
+            .count()
+    }
+
+    #[test]
+    fn coins_and_game_over_play_without_an_audio_backend() {
+        let mut app = headless_app();
+        let snake = app.world.spawn_empty().id();
+
+        app.world.send_event(CoinCollectedEvent {
+            position: Vec2::ZERO,
+            snake,
+        });
+        app.update();
+        assert_eq!(playing(&mut app, Sfx::Coin), 1);
+
+        app.world.send_event(GameOverEvent);
+        app.update();
+        app.world.send_event(GameOverEvent);
+        app.update();
+        assert_eq!(playing(&mut app, Sfx::GameOver), 1);
+    }
+
+    #[test]
+    fn sound_caps_hold_without_an_audio_backend() {
+        let mut app = headless_app();
+        let snake = app.world.spawn_empty().id();
+        for _ in 0..10 {
+            app.world.send_event(CoinCollectedEvent {
+                position: Vec2::ZERO,
+                snake,
+            });
         }
+        app.update();
+        assert_eq!(playing(&mut app, Sfx::Coin), Sfx::Coin.max_instances());
     }
 }
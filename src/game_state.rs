@@ -1,10 +1,11 @@
 use bevy::prelude::*;
 
 use crate::{
+    boost::Pellet,
     coin::Coin,
-    event::GameOverEvent,
-    game_audio::{CoinColledtedAudio, GameOverAudio, GameOverAudioPlayed},
-    snake::{SnakeHead, SnakeSegment, SnakeSegments, SnakeSpeed},
+    event::{GameOverEvent, NewRoundEvent},
+    settings::Settings,
+    snake::Player,
 };
 
 pub struct GameStatePlugin;
@@ -12,8 +13,8 @@ pub struct GameStatePlugin;
 impl Plugin for GameStatePlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<GameState>()
-            .insert_resource(Score(0))
             .add_systems(Startup, (setup_reset_button, setup_score_label))
+            .add_systems(OnExit(GameState::GameOver), hide_reset_button)
             .add_systems(
                 Update,
                 (
@@ -28,7 +29,8 @@ impl Plugin for GameStatePlugin {
 
 const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
 
-#[derive(Resource)]
+/// Points a snake has scored this round.
+#[derive(Component)]
 pub struct Score(pub u32);
 
 #[derive(Component)]
@@ -37,6 +39,9 @@ pub struct ScoreText;
 #[derive(Component)]
 pub struct ResetButton;
 
+#[derive(Component)]
+struct PlayAgainButton;
+
 #[derive(States, Default, Debug, Hash, Eq, PartialEq, Clone, Copy)]
 pub enum GameState {
     #[default]
@@ -45,6 +50,38 @@ pub enum GameState {
     GameOver,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    /// One mouse-controlled snake.
+    Solo,
+    /// Two snakes sharing the keyboard, playing rounds until one wins the match.
+    Versus,
+}
+
+impl GameMode {
+    const ALL: [GameMode; 2] = [GameMode::Solo, GameMode::Versus];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GameMode::Solo => "Solo",
+            GameMode::Versus => "Versus",
+        }
+    }
+
+    pub fn cycle(&self, step: i32) -> GameMode {
+        let index = Self::ALL.iter().position(|mode| mode == self).unwrap_or(0) as i32;
+        Self::ALL[(index + step).rem_euclid(Self::ALL.len() as i32) as usize]
+    }
+
+    /// Whether the game or round ends with `alive` snakes left.
+    pub fn round_over(&self, alive: usize) -> bool {
+        match self {
+            GameMode::Solo => alive == 0,
+            GameMode::Versus => alive <= 1,
+        }
+    }
+}
+
 fn game_state_system(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     state: Res<State<GameState>>,
@@ -67,40 +104,51 @@ fn reset_game(
     mut commands: Commands,
     mut next_state: ResMut<NextState<GameState>>,
     mut game_over_event: EventReader<GameOverEvent>,
-    coin_query: Query<Entity, With<Coin>>,
-    segment_query: Query<(Entity, &Transform, &SnakeSegment), Without<SnakeHead>>,
-    mut score: ResMut<Score>,
+    food_query: Query<Entity, Or<(With<Coin>, With<Pellet>)>>,
     mut reset_button_query: Query<&mut Visibility, With<ResetButton>>,
-    game_over_audio_query: Query<Entity, With<GameOverAudio>>,
-    coin_collected_query: Query<Entity, With<CoinColledtedAudio>>,
+    settings: Res<Settings>,
 ) {
     for _ in game_over_event.read() {
         next_state.set(GameState::GameOver);
-        for entity in coin_query.iter() {
+        for entity in food_query.iter() {
             commands.entity(entity).despawn();
         }
 
-        for (entity, _, _) in segment_query.iter() {
-            commands.entity(entity).despawn();
-        }
-
-        for entity in game_over_audio_query.iter() {
-            commands.entity(entity).despawn();
-        }
-
-        for entity in coin_collected_query.iter() {
-            commands.entity(entity).despawn();
+        // Versus rounds end on the match screen instead.
+        if settings.game_mode == GameMode::Solo {
+            let mut reset_button = reset_button_query.single_mut();
+            *reset_button = Visibility::Visible;
         }
+    }
+}
 
-        let mut reset_button = reset_button_query.single_mut();
-        *reset_button = Visibility::Visible;
-        score.0 = 0;
+fn hide_reset_button(mut reset_button_query: Query<&mut Visibility, With<ResetButton>>) {
+    for mut visibility in reset_button_query.iter_mut() {
+        *visibility = Visibility::Hidden;
     }
 }
 
-fn score_update_system(mut query: Query<&mut Text, With<ScoreText>>, score: ResMut<Score>) {
+/// Keeps showing the last scores after the snakes are gone, until the next round.
+fn score_update_system(
+    mut query: Query<&mut Text, With<ScoreText>>,
+    score_query: Query<(&Player, &Score)>,
+    settings: Res<Settings>,
+) {
+    if score_query.is_empty() {
+        return;
+    }
+    let mut scores: Vec<(&Player, &Score)> = score_query.iter().collect();
+    scores.sort_by_key(|(player, _)| **player);
+    let value = match settings.game_mode {
+        GameMode::Solo => format!("{}", scores[0].1 .0),
+        GameMode::Versus => scores
+            .iter()
+            .map(|(player, score)| format!("{} {}", player.label(), score.0))
+            .collect::<Vec<_>>()
+            .join("  "),
+    };
     for mut text in &mut query {
-        text.sections[1].value = format!("{}", score.0);
+        text.sections[1].value = value.clone();
     }
 }
 
@@ -112,23 +160,16 @@ fn click_reset_button(
             &mut BorderColor,
             &Children,
         ),
-        (Changed<Interaction>, With<Button>),
+        (Changed<Interaction>, With<PlayAgainButton>),
     >,
-    mut reset_button_query: Query<&mut Visibility, With<ResetButton>>,
     mut next_state: ResMut<NextState<GameState>>,
-    mut snake_segments: ResMut<SnakeSegments>,
-    mut snake_speed: ResMut<SnakeSpeed>,
-    mut audio_played: ResMut<GameOverAudioPlayed>,
+    mut new_round_event: EventWriter<NewRoundEvent>,
 ) {
     for (interaction, _color, _border_color, _children) in &mut interaction_query {
         if interaction == &Interaction::Pressed {
             info!("Button pressed");
-            let mut reset_button = reset_button_query.single_mut();
-            *reset_button = Visibility::Hidden;
             next_state.set(GameState::InGame);
-            snake_segments.0.truncate(1);
-            snake_speed.0 = 200.0;
-            audio_played.0 = false;
+            new_round_event.send(NewRoundEvent);
         }
     }
 }
@@ -151,19 +192,22 @@ fn setup_reset_button(mut commands: Commands, asset_server: Res<AssetServer>) {
         ))
         .with_children(|parent| {
             parent
-                .spawn(ButtonBundle {
-                    style: Style {
-                        width: Val::Px(250.0),
-                        height: Val::Px(65.0),
-                        border: UiRect::all(Val::Px(5.0)),
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(250.0),
+                            height: Val::Px(65.0),
+                            border: UiRect::all(Val::Px(5.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        border_color: BorderColor(Color::WHITE),
+                        background_color: NORMAL_BUTTON.into(),
                         ..default()
                     },
-                    border_color: BorderColor(Color::WHITE),
-                    background_color: NORMAL_BUTTON.into(),
-                    ..default()
-                })
+                    PlayAgainButton,
+                ))
                 .with_children(|parent| {
                     parent.spawn(TextBundle::from_section(
                         "Play Again",
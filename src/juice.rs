@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+
+use crate::{
+    event::{CoinCollectedEvent, GameOverEvent},
+    settings::Settings,
+};
+
+pub struct JuicePlugin;
+
+impl Plugin for JuicePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(JuiceConfig::default())
+            .insert_resource(Trauma::default())
+            .insert_resource(HitStop(None))
+            .add_systems(Update, (add_trauma, decay_trauma, update_hit_stop).chain());
+    }
+}
+
+#[derive(Resource)]
+pub struct JuiceConfig {
+    /// Trauma added when the snake dies, out of 1.
+    pub game_over_trauma: f32,
+    /// Trauma added for each coin eaten. Boost pellets are too small to shake the view.
+    pub coin_trauma: f32,
+    /// Trauma lost per second.
+    pub trauma_decay: f32,
+    /// Camera offset in pixels at full trauma.
+    pub max_offset: f32,
+    /// Camera roll in radians at full trauma.
+    pub max_angle: f32,
+    /// How fast the shake wobbles, in cycles per second.
+    pub frequency: f32,
+    /// Real seconds the game clock is frozen for when the snake dies.
+    pub hit_stop: f32,
+}
+
+impl Default for JuiceConfig {
+    fn default() -> Self {
+        Self {
+            game_over_trauma: 0.8,
+            coin_trauma: 0.15,
+            trauma_decay: 1.5,
+            max_offset: 24.0,
+            max_angle: 0.05,
+            frequency: 25.0,
+            hit_stop: 0.12,
+        }
+    }
+}
+
+/// Camera trauma in `0..=1`; the shake grows with its square so small kicks stay subtle.
+#[derive(Resource, Default)]
+pub struct Trauma(f32);
+
+impl Trauma {
+    fn add(&mut self, amount: f32) {
+        self.0 = (self.0 + amount).min(1.0);
+    }
+
+    /// Camera offset and roll for the current trauma at `seconds` of real time.
+    pub fn shake(&self, config: &JuiceConfig, intensity: f32, seconds: f32) -> (Vec2, f32) {
+        let shake = self.0 * self.0 * intensity;
+        if shake <= 0.0 {
+            return (Vec2::ZERO, 0.0);
+        }
+        // Sums of incommensurate sines stand in for smooth noise, one per channel.
+        let wobble = |seed: f32| {
+            let t = seconds * config.frequency + seed;
+            (t.sin() + (t * 2.3 + 1.7).sin() * 0.5) / 1.5
+        };
+        let offset = Vec2::new(wobble(0.0), wobble(31.0)) * config.max_offset * shake;
+        (offset, wobble(67.0) * config.max_angle * shake)
+    }
+}
+
+/// Real time left on the current hit-stop freeze.
+#[derive(Resource)]
+struct HitStop(Option<Timer>);
+
+fn add_trauma(
+    mut coin_collected_event: EventReader<CoinCollectedEvent>,
+    mut game_over_event: EventReader<GameOverEvent>,
+    mut trauma: ResMut<Trauma>,
+    mut hit_stop: ResMut<HitStop>,
+    mut time: ResMut<Time<Virtual>>,
+    config: Res<JuiceConfig>,
+    settings: Res<Settings>,
+) {
+    let coins = coin_collected_event.read().count();
+    let game_over = game_over_event.read().count() > 0;
+    if settings.reduced_motion {
+        return;
+    }
+    trauma.add(coins as f32 * config.coin_trauma);
+    if game_over {
+        trauma.add(config.game_over_trauma);
+        if config.hit_stop > 0.0 {
+            time.pause();
+            hit_stop.0 = Some(Timer::from_seconds(config.hit_stop, TimerMode::Once));
+        }
+    }
+}
+
+fn decay_trauma(
+    mut trauma: ResMut<Trauma>,
+    config: Res<JuiceConfig>,
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+) {
+    if settings.reduced_motion {
+        trauma.0 = 0.0;
+        return;
+    }
+    trauma.0 = (trauma.0 - config.trauma_decay * time.delta_seconds()).max(0.0);
+}
+
+/// Counts the freeze down on the real clock, since the virtual one is paused.
+fn update_hit_stop(
+    mut hit_stop: ResMut<HitStop>,
+    mut time: ResMut<Time<Virtual>>,
+    real_time: Res<Time<Real>>,
+) {
+    let Some(timer) = hit_stop.0.as_mut() else {
+        return;
+    };
+    if timer.tick(real_time.delta()).finished() {
+        time.unpause();
+        hit_stop.0 = None;
+    }
+}
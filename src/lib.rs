@@ -0,0 +1,5 @@
+//! Game logic that runs without a window, for tools and tests built on the game.
+
+pub mod cli;
+pub mod net;
+pub mod sim;
@@ -1,51 +1,106 @@
+// Bevy systems routinely take many parameters and nested query filters.
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+mod accessibility;
+mod arena;
+mod boost;
+mod bot;
+mod camera;
 mod coin;
+mod combo;
 mod event;
 mod fps;
 mod game_audio;
 mod game_state;
+mod juice;
+mod movement_audio;
+mod music;
+mod online;
+mod particles;
+mod power_up;
+mod settings;
 mod snake;
+mod snake_render;
+mod spectate;
+mod theme;
+mod versus;
 
+use accessibility::AccessibilityPlugin;
+use arena::ArenaPlugin;
 use bevy::{input::common_conditions::input_toggle_active, prelude::*};
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use boost::BoostPlugin;
+use bot::BotPlugin;
+use camera::CameraPlugin;
 use coin::CoinPlugin;
+use combo::ComboPlugin;
 use event::EventPlugin;
 use fps::FpsPlugin;
 use game_audio::GameAudioPlugin;
 use game_state::GameStatePlugin;
+use juice::JuicePlugin;
+use movement_audio::MovementAudioPlugin;
+use music::MusicPlugin;
+use online::{OnlineOptions, OnlinePlugin};
+use particles::ParticlePlugin;
+use power_up::PowerUpPlugin;
+use settings::SettingsPlugin;
 use snake::SnakePlugin;
+use snake_render::SnakeRenderPlugin;
+use theme::ThemePlugin;
+use versus::VersusPlugin;
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Awesome Snake Game".into(),
-                resizable: true,
-                position: WindowPosition::Centered(MonitorSelection::Primary),
-                ..default()
-            }),
-            ..default()
-        }))
-        .add_plugins(
-            WorldInspectorPlugin::default().run_if(input_toggle_active(false, KeyCode::Escape)),
-        )
-        .add_plugins((
-            SnakePlugin,
-            GameStatePlugin,
-            EventPlugin,
-            FpsPlugin,
-            CoinPlugin,
-            GameAudioPlugin,
-        ))
-        .add_systems(Startup, setup_camera2d)
-        .run();
-}
+    let online = match OnlineOptions::from_args().transpose() {
+        Ok(online) => online,
+        Err(error) => {
+            eprintln!("bevy-snake: {error}");
+            std::process::exit(2);
+        }
+    };
 
-fn setup_camera2d(mut commands: Commands, _asset_server: Res<AssetServer>) {
-    commands.spawn(Camera2dBundle {
-        transform: Transform {
-            translation: Vec3::new(0.0, 0.0, 0.0),
-            ..Default::default()
-        },
-        ..Default::default()
-    });
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            title: "Awesome Snake Game".into(),
+            resizable: true,
+            position: WindowPosition::Centered(MonitorSelection::Primary),
+            ..default()
+        }),
+        ..default()
+    }));
+    // Joining a server or watching a replay swaps the local game for a view of that one.
+    if let Some(online) = online {
+        app.add_plugins(OnlinePlugin(online)).run();
+        return;
+    }
+    app.add_plugins(
+        WorldInspectorPlugin::default().run_if(input_toggle_active(false, KeyCode::Escape)),
+    )
+    .add_plugins((
+        SnakePlugin,
+        GameStatePlugin,
+        EventPlugin,
+        CoinPlugin,
+        ComboPlugin,
+        PowerUpPlugin,
+        BoostPlugin,
+        ArenaPlugin,
+        JuicePlugin,
+        VersusPlugin,
+        BotPlugin,
+    ))
+    .add_plugins((
+        FpsPlugin,
+        GameAudioPlugin,
+        MusicPlugin,
+        MovementAudioPlugin,
+        SnakeRenderPlugin,
+        ThemePlugin,
+        SettingsPlugin,
+        AccessibilityPlugin,
+        ParticlePlugin,
+        CameraPlugin,
+    ))
+    .run();
 }
@@ -0,0 +1,169 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    event::SnakeCollideEvent,
+    game_audio::{PlaySfx, Sfx},
+    game_state::GameState,
+    power_up::{ActiveEffects, PowerUpKind},
+    snake::{SnakeHead, SnakeSegment, SnakeSegments},
+};
+
+pub struct MovementAudioPlugin;
+
+impl Plugin for MovementAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MovementAudioConfig::default())
+            .insert_resource(MovementAudio::default())
+            .add_systems(OnEnter(GameState::InGame), reset_movement_audio)
+            .add_systems(
+                Update,
+                (play_slither_and_turn, play_grow_blips, play_near_misses)
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct MovementAudioConfig {
+    /// Distance the head travels between slither sounds, so faster snakes slither faster.
+    pub slither_distance: f32,
+    /// Turn rate in radians per second that counts as a sharp turn.
+    pub sharp_turn_rate: f32,
+    pub turn_cooldown: f32,
+    /// Head-to-body distance that counts as a near miss, outside the collision range.
+    pub near_miss_distance: f32,
+    /// Segments right behind the head are always close, so they never count.
+    pub near_miss_skip: usize,
+    pub near_miss_cooldown: f32,
+}
+
+impl Default for MovementAudioConfig {
+    fn default() -> Self {
+        Self {
+            slither_distance: 60.0,
+            sharp_turn_rate: 4.0,
+            turn_cooldown: 0.4,
+            near_miss_distance: 18.0,
+            near_miss_skip: 8,
+            near_miss_cooldown: 1.0,
+        }
+    }
+}
+
+/// Head motion of each snake from the last frame.
+#[derive(Default)]
+struct HeadMotion {
+    position: Vec2,
+    heading: Vec2,
+    distance: f32,
+}
+
+/// Per-snake head motion and time since each sound last played.
+#[derive(Resource, Default)]
+struct MovementAudio {
+    heads: HashMap<Entity, HeadMotion>,
+    since_turn: f32,
+    since_near_miss: f32,
+}
+
+fn reset_movement_audio(mut movement_audio: ResMut<MovementAudio>) {
+    movement_audio.heads.clear();
+}
+
+fn play_slither_and_turn(
+    mut movement_audio: ResMut<MovementAudio>,
+    mut play_sfx: EventWriter<PlaySfx>,
+    head_query: Query<(Entity, &Transform), With<SnakeHead>>,
+    config: Res<MovementAudioConfig>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds();
+    if delta <= 0.0 {
+        return;
+    }
+    movement_audio.since_turn += delta;
+    movement_audio
+        .heads
+        .retain(|entity, _| head_query.contains(*entity));
+
+    let mut slither = false;
+    let mut turn = false;
+    for (entity, head_transform) in head_query.iter() {
+        let position = head_transform.translation.truncate();
+        let heading = (head_transform.rotation * Vec3::X).truncate();
+
+        if let Some(motion) = movement_audio.heads.get_mut(&entity) {
+            motion.distance += position.distance(motion.position);
+            if motion.distance >= config.slither_distance {
+                motion.distance %= config.slither_distance;
+                slither = true;
+            }
+            let turn_rate = motion.heading.angle_between(heading).abs() / delta;
+            turn |= turn_rate >= config.sharp_turn_rate;
+            motion.position = position;
+            motion.heading = heading;
+        } else {
+            movement_audio.heads.insert(
+                entity,
+                HeadMotion {
+                    position,
+                    heading,
+                    distance: 0.0,
+                },
+            );
+        }
+    }
+
+    if slither {
+        play_sfx.send(PlaySfx(Sfx::Slither));
+    }
+    if turn && movement_audio.since_turn >= config.turn_cooldown {
+        movement_audio.since_turn = 0.0;
+        play_sfx.send(PlaySfx(Sfx::Turn));
+    }
+}
+
+fn play_grow_blips(
+    mut snake_collide_event: EventReader<SnakeCollideEvent>,
+    mut play_sfx: EventWriter<PlaySfx>,
+) {
+    for _ in snake_collide_event.read() {
+        play_sfx.send(PlaySfx(Sfx::Grow));
+    }
+}
+
+/// Plays when a head grazes its own body without hitting it.
+fn play_near_misses(
+    mut movement_audio: ResMut<MovementAudio>,
+    mut play_sfx: EventWriter<PlaySfx>,
+    head_query: Query<(&Transform, &SnakeSegments, &ActiveEffects), With<SnakeHead>>,
+    segment_query: Query<(&Transform, &SnakeSegment), Without<SnakeHead>>,
+    config: Res<MovementAudioConfig>,
+    time: Res<Time>,
+) {
+    movement_audio.since_near_miss += time.delta_seconds();
+    if movement_audio.since_near_miss < config.near_miss_cooldown {
+        return;
+    }
+
+    let near_miss = head_query
+        .iter()
+        .filter(|(_, _, effects)| !effects.is_active(PowerUpKind::Ghost))
+        .any(|(head_transform, segments, _)| {
+            let head_position = head_transform.translation.truncate();
+            segments
+                .0
+                .iter()
+                .skip(config.near_miss_skip)
+                .filter_map(|segment| segment_query.get(*segment).ok())
+                .any(|(transform, segment)| {
+                    segment.collides()
+                        && transform.translation.truncate().distance(head_position)
+                            < config.near_miss_distance
+                })
+        });
+    if near_miss {
+        movement_audio.since_near_miss = 0.0;
+        play_sfx.send(PlaySfx(Sfx::NearMiss));
+    }
+}
@@ -0,0 +1,176 @@
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{game_state::GameState, settings::Settings, snake::SnakeSpeed};
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MusicConfig::default()).add_systems(
+            Update,
+            (switch_music_track, fade_music, update_music_intensity).chain(),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicTrack {
+    Menu,
+    InGame,
+    GameOver,
+}
+
+impl MusicTrack {
+    fn for_state(state: &GameState) -> MusicTrack {
+        match state {
+            GameState::InGame => MusicTrack::InGame,
+            GameState::Paused => MusicTrack::Menu,
+            GameState::GameOver => MusicTrack::GameOver,
+        }
+    }
+
+    fn path(&self) -> &'static str {
+        match self {
+            MusicTrack::Menu => "audio/music_menu.wav",
+            MusicTrack::InGame => "audio/music_game.wav",
+            MusicTrack::GameOver => "audio/music_game_over.wav",
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct MusicConfig {
+    /// Mix level of the music, under the music volume setting.
+    pub volume: f32,
+    /// Seconds for one track to fade out while the next fades in.
+    pub crossfade: f32,
+    /// Snake speed at which the in-game track plays at its normal rate.
+    pub base_speed: f32,
+    /// Extra playback rate per unit of snake speed above `base_speed`, relative to it.
+    pub speed_to_rate: f32,
+    pub max_rate: f32,
+    /// How quickly the playback rate follows the snake speed, per second.
+    pub rate_smoothing: f32,
+}
+
+impl Default for MusicConfig {
+    fn default() -> Self {
+        Self {
+            volume: 0.5,
+            crossfade: 1.5,
+            base_speed: 200.0,
+            speed_to_rate: 0.5,
+            max_rate: 1.4,
+            rate_smoothing: 1.0,
+        }
+    }
+}
+
+/// A playing music track and how far it has faded in.
+#[derive(Component)]
+pub struct Music {
+    pub track: MusicTrack,
+    /// Fade level in `0..=1`, multiplied with the music volume.
+    fade: f32,
+    fading_out: bool,
+}
+
+/// Starts the track for the new state and fades out every other one.
+fn switch_music_track(
+    mut commands: Commands,
+    asset_server: Option<Res<AssetServer>>,
+    state: Res<State<GameState>>,
+    mut music_query: Query<&mut Music>,
+) {
+    // Headless runs may leave out the asset server; there is no music then.
+    let Some(asset_server) = asset_server else {
+        return;
+    };
+    if !state.is_changed() {
+        return;
+    }
+    let track = MusicTrack::for_state(state.get());
+
+    let mut already_playing = false;
+    for mut music in music_query.iter_mut() {
+        if music.track == track {
+            // Coming back before a fade-out finished picks the track up where it is.
+            music.fading_out = false;
+            already_playing = true;
+        } else {
+            music.fading_out = true;
+        }
+    }
+    if already_playing {
+        return;
+    }
+
+    commands.spawn((
+        AudioBundle {
+            source: asset_server.load(track.path()),
+            settings: PlaybackSettings::LOOP.with_volume(Volume::new(0.0)),
+        },
+        Music {
+            track,
+            fade: 0.0,
+            fading_out: false,
+        },
+        Name::new("Music"),
+    ));
+}
+
+/// Fades on the real clock so hit-stop and the game speed setting don't stretch them.
+fn fade_music(
+    mut commands: Commands,
+    mut music_query: Query<(Entity, &mut Music, Option<&AudioSink>)>,
+    config: Res<MusicConfig>,
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+) {
+    let step = time.delta_seconds() / config.crossfade.max(f32::EPSILON);
+    for (entity, mut music, sink) in music_query.iter_mut() {
+        // The sink only appears once the track has loaded and started playing.
+        let Some(sink) = sink else {
+            if music.fading_out {
+                commands.entity(entity).despawn();
+            }
+            continue;
+        };
+        // Tracks held by the pause menu keep their level until they resume.
+        if sink.is_paused() {
+            continue;
+        }
+        if music.fading_out {
+            music.fade -= step;
+            if music.fade <= 0.0 {
+                commands.entity(entity).despawn();
+                continue;
+            }
+        } else {
+            music.fade = (music.fade + step).min(1.0);
+        }
+        sink.set_volume(music.fade * config.volume * settings.music_volume());
+    }
+}
+
+/// Speeds the in-game track up as the fastest snake gets faster.
+fn update_music_intensity(
+    music_query: Query<(&Music, &AudioSink)>,
+    snake_query: Query<&SnakeSpeed>,
+    config: Res<MusicConfig>,
+    time: Res<Time<Real>>,
+) {
+    let snake_speed = snake_query
+        .iter()
+        .map(|speed| speed.0)
+        .fold(config.base_speed, f32::max);
+    let extra_speed = (snake_speed - config.base_speed).max(0.0) / config.base_speed;
+    let target_rate = (1.0 + extra_speed * config.speed_to_rate).min(config.max_rate);
+    let blend = 1.0 - (-config.rate_smoothing * time.delta_seconds()).exp();
+
+    for (music, sink) in music_query.iter() {
+        if music.track == MusicTrack::InGame {
+            sink.set_speed(sink.speed() + (target_rate - sink.speed()) * blend);
+        }
+    }
+}
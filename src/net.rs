@@ -0,0 +1,19 @@
+//! Online play: a headless server runs the only authoritative copy of the
+//! [`crate::sim`] rules, and clients send it their steering over UDP. Clients
+//! predict their own head so it answers the controls at once, correct it when
+//! the server disagrees, and draw everyone else slightly in the past,
+//! interpolated between snapshots.
+//!
+//! Spectators join the same server without a snake and get the same snapshots,
+//! and a server can record them to a file to be watched again as a [`Replay`].
+
+mod client;
+mod link;
+pub mod protocol;
+mod replay;
+mod server;
+
+pub use client::{Client, ClientState, ClientStats, NetFrame, NetSnake};
+pub use link::{Link, LinkStats, NetConditions};
+pub use replay::{Replay, ReplayHeader};
+pub use server::{Server, ServerConfig};
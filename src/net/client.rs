@@ -0,0 +1,643 @@
+use std::{
+    collections::VecDeque,
+    io,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use bevy::math::Vec2;
+
+use super::{
+    link::{Link, NetConditions},
+    protocol::{
+        decode, encode, ClientPacket, InputFrame, ServerPacket, SnakeState, Snapshot,
+        PROTOCOL_VERSION,
+    },
+};
+use crate::sim::{advance_head, steer_heading, BodyPath, Rules, Steer, WorldView};
+
+/// How often an unanswered hello is repeated.
+const HELLO_INTERVAL: Duration = Duration::from_millis(250);
+/// How often a spectator reminds the server it's still watching.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(1);
+/// Most inputs repeated in one packet while waiting for acknowledgement.
+const MAX_RESENT_INPUTS: usize = 32;
+/// Snapshots kept for interpolation.
+const SNAPSHOT_HISTORY: usize = 32;
+/// How quickly a prediction error is smoothed away on screen, per second.
+const CORRECTION_SMOOTHING: f32 = 12.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientState {
+    Connecting,
+    Connected {
+        player: u8,
+    },
+    /// Seated as a spectator.
+    Watching,
+    Rejected(String),
+}
+
+/// How the connection has been doing, for a HUD or a test report.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ClientStats {
+    pub snapshots: u64,
+    /// Snapshots that never arrived, going by gaps in their ticks.
+    pub snapshots_missed: u64,
+    /// Snapshots that arrived after a newer one.
+    pub snapshots_late: u64,
+    /// Smoothed time from sending an input to seeing it acknowledged, in seconds.
+    pub round_trip: f32,
+    /// Times the server disagreed with the predicted head.
+    pub corrections: u64,
+    /// Sum and worst of those disagreements, in world units.
+    pub correction_total: f32,
+    pub correction_max: f32,
+}
+
+/// The local head, run ahead of the server with inputs it hasn't applied yet.
+#[derive(Debug, Clone)]
+struct Prediction {
+    round: u32,
+    head: Vec2,
+    direction: Vec2,
+    speed: f32,
+    /// Segments after the head, from the newest snapshot.
+    body_length: usize,
+    path: BodyPath,
+}
+
+/// A snake as it should be drawn right now.
+#[derive(Debug, Clone)]
+pub struct NetSnake {
+    pub id: u8,
+    pub bot: bool,
+    pub name: String,
+    pub alive: bool,
+    pub score: u32,
+    /// Head first.
+    pub segments: Vec<Vec2>,
+    /// This client's own snake, drawn from its prediction. Never set for
+    /// spectators or replays.
+    pub local: bool,
+}
+
+/// The arena as it should be drawn right now.
+#[derive(Debug, Clone)]
+pub struct NetFrame {
+    pub round: u32,
+    pub round_over: bool,
+    pub snakes: Vec<NetSnake>,
+    pub coins: Vec<Vec2>,
+}
+
+/// One player's or spectator's connection to a [`super::Server`]. Call
+/// [`Client::update`] every frame with the player's steering and draw
+/// [`Client::frame`].
+pub struct Client {
+    link: Link,
+    server: SocketAddr,
+    name: String,
+    spectate: bool,
+    pub state: ClientState,
+    rules: Rules,
+    tick_rate: u32,
+    snapshot_interval: u32,
+    /// How far behind the server's clock other snakes are drawn, so there is
+    /// usually a newer snapshot to blend toward.
+    pub interpolation_delay: Duration,
+    started: Instant,
+    /// Last hello, or last keep-alive once watching.
+    last_hello: Option<Instant>,
+    last_update: Option<Instant>,
+    next_input_at: Option<Instant>,
+    next_seq: u32,
+    /// Inputs sent but not yet acknowledged, oldest first, with when they were sent.
+    pending: VecDeque<(InputFrame, Instant)>,
+    /// Oldest first.
+    snapshots: Vec<Snapshot>,
+    /// Local seconds at which the server's tick 0 would have arrived, estimated
+    /// from the least delayed snapshots.
+    clock_offset: Option<f64>,
+    prediction: Option<Prediction>,
+    /// Added to the drawn head after a correction, shrinking to nothing.
+    correction: Vec2,
+    pub stats: ClientStats,
+}
+
+impl Client {
+    /// Starts saying hello to `server`. Nothing blocks; watch [`Client::state`].
+    pub fn connect(
+        server: SocketAddr,
+        name: &str,
+        conditions: NetConditions,
+    ) -> io::Result<Client> {
+        Self::open(server, name, conditions, false)
+    }
+
+    /// Like [`Client::connect`], but asks to watch instead of play.
+    pub fn spectate(
+        server: SocketAddr,
+        name: &str,
+        conditions: NetConditions,
+    ) -> io::Result<Client> {
+        Self::open(server, name, conditions, true)
+    }
+
+    fn open(
+        server: SocketAddr,
+        name: &str,
+        conditions: NetConditions,
+        spectate: bool,
+    ) -> io::Result<Client> {
+        let local = if server.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        Ok(Client {
+            link: Link::bind(local, conditions)?,
+            server,
+            name: name.to_string(),
+            spectate,
+            state: ClientState::Connecting,
+            rules: Rules::default(),
+            tick_rate: 60,
+            snapshot_interval: 1,
+            interpolation_delay: Duration::from_millis(100),
+            started: Instant::now(),
+            last_hello: None,
+            last_update: None,
+            next_input_at: None,
+            next_seq: 1,
+            pending: VecDeque::new(),
+            snapshots: Vec::new(),
+            clock_offset: None,
+            prediction: None,
+            correction: Vec2::ZERO,
+            stats: ClientStats::default(),
+        })
+    }
+
+    pub fn link_stats(&self) -> super::LinkStats {
+        self.link.stats
+    }
+
+    pub fn player(&self) -> Option<u8> {
+        match self.state {
+            ClientState::Connected { player } => Some(player),
+            _ => None,
+        }
+    }
+
+    /// Reads what the server sent, and sends one input per server tick that
+    /// has passed since the last call, all steering with `steer`.
+    pub fn update(&mut self, now: Instant, steer: Steer) {
+        self.receive(now);
+        let delta = self.last_update.map_or(0.0, |last| {
+            now.saturating_duration_since(last).as_secs_f32()
+        });
+        self.last_update = Some(now);
+        self.correction *= (-CORRECTION_SMOOTHING * delta).exp();
+
+        match self.state {
+            ClientState::Connecting => {
+                if self
+                    .last_hello
+                    .is_none_or(|last| now - last >= HELLO_INTERVAL)
+                {
+                    self.last_hello = Some(now);
+                    let hello = ClientPacket::Hello {
+                        version: PROTOCOL_VERSION,
+                        name: self.name.clone(),
+                        spectate: self.spectate,
+                    };
+                    self.link.send(self.server, encode(&hello), now);
+                }
+            }
+            ClientState::Connected { .. } => self.send_inputs(now, steer),
+            ClientState::Watching => {
+                if self
+                    .last_hello
+                    .is_none_or(|last| now - last >= KEEP_ALIVE_INTERVAL)
+                {
+                    self.last_hello = Some(now);
+                    let keep_alive = encode(&ClientPacket::KeepAlive);
+                    self.link.send(self.server, keep_alive, now);
+                }
+            }
+            ClientState::Rejected(_) => {}
+        }
+        self.link.flush(now);
+    }
+
+    /// Tells the server we're leaving, without waiting for the simulated latency.
+    pub fn disconnect(&mut self) {
+        if matches!(
+            self.state,
+            ClientState::Connected { .. } | ClientState::Watching
+        ) {
+            self.link.conditions = NetConditions::default();
+            let now = Instant::now();
+            self.link.send(self.server, encode(&ClientPacket::Bye), now);
+        }
+    }
+
+    fn tick_length(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.tick_rate.max(1) as f64)
+    }
+
+    fn send_inputs(&mut self, now: Instant, steer: Steer) {
+        let tick_length = self.tick_length();
+        let mut next_input_at = self.next_input_at.unwrap_or(now);
+        // After a long stall, skip ahead rather than flood the server.
+        if now.saturating_duration_since(next_input_at) > tick_length * 10 {
+            next_input_at = now;
+        }
+        let mut sent_any = false;
+        while next_input_at <= now {
+            let input = InputFrame {
+                seq: self.next_seq,
+                steer: steer.into(),
+            };
+            self.next_seq += 1;
+            self.pending.push_back((input, now));
+            if let Some(prediction) = self.prediction.as_mut() {
+                predict(prediction, &self.rules, steer, tick_length.as_secs_f32());
+            }
+            next_input_at += tick_length;
+            sent_any = true;
+        }
+        self.next_input_at = Some(next_input_at);
+        while self.pending.len() > MAX_RESENT_INPUTS * 4 {
+            self.pending.pop_front();
+        }
+        if sent_any {
+            let skip = self.pending.len().saturating_sub(MAX_RESENT_INPUTS);
+            let inputs = self.pending.iter().skip(skip).map(|(input, _)| *input);
+            let packet = ClientPacket::Input {
+                inputs: inputs.collect(),
+            };
+            self.link.send(self.server, encode(&packet), now);
+        }
+    }
+
+    fn receive(&mut self, now: Instant) {
+        while let Some(packet) = self
+            .link
+            .receive()
+            .and_then(|(from, bytes)| (from == self.server).then(|| decode(bytes)).flatten())
+        {
+            match packet {
+                ServerPacket::Welcome {
+                    player,
+                    tick_rate,
+                    snapshot_interval,
+                    difficulty,
+                    ..
+                } => {
+                    if self.state == ClientState::Connecting {
+                        self.state = match player {
+                            Some(player) => ClientState::Connected { player },
+                            None => ClientState::Watching,
+                        };
+                        self.rules = Rules::for_difficulty(difficulty);
+                        self.tick_rate = tick_rate;
+                        self.snapshot_interval = snapshot_interval.max(1);
+                    }
+                }
+                ServerPacket::Reject { reason } => {
+                    if self.state == ClientState::Connecting {
+                        self.state = ClientState::Rejected(reason);
+                    }
+                }
+                ServerPacket::Snapshot(snapshot) => self.receive_snapshot(snapshot, now),
+            }
+        }
+    }
+
+    fn receive_snapshot(&mut self, snapshot: Snapshot, now: Instant) {
+        let player = match self.state {
+            ClientState::Connected { player } => Some(player),
+            ClientState::Watching => None,
+            _ => return,
+        };
+        self.stats.snapshots += 1;
+        let newest = self.snapshots.last().map(|newest| newest.tick);
+        if let Some(newest) = newest {
+            if snapshot.tick <= newest {
+                self.stats.snapshots_late += 1;
+                if self.snapshots.iter().all(|kept| kept.tick != snapshot.tick) {
+                    let index = self
+                        .snapshots
+                        .iter()
+                        .position(|kept| kept.tick > snapshot.tick)
+                        .unwrap_or(self.snapshots.len());
+                    self.snapshots.insert(index, snapshot);
+                }
+                return;
+            }
+            let gap = (snapshot.tick - newest) / self.snapshot_interval as u64;
+            self.stats.snapshots_missed += gap.saturating_sub(1);
+        }
+
+        let arrived = (now - self.started).as_secs_f64();
+        let sample = arrived - snapshot.tick as f64 / self.tick_rate as f64;
+        self.clock_offset = Some(match self.clock_offset {
+            // Jump to a faster arrival at once, and drift slowly toward slower
+            // ones, so the estimate follows the least delayed path.
+            Some(offset) if sample > offset => offset + (sample - offset) * 0.02,
+            _ => sample,
+        });
+
+        if let Some(position) = self
+            .pending
+            .iter()
+            .position(|(input, _)| input.seq == snapshot.ack)
+        {
+            let round_trip = (now - self.pending[position].1).as_secs_f32();
+            self.stats.round_trip = if self.stats.round_trip == 0.0 {
+                round_trip
+            } else {
+                self.stats.round_trip + (round_trip - self.stats.round_trip) * 0.1
+            };
+        }
+        self.pending.retain(|(input, _)| input.seq > snapshot.ack);
+        if let Some(player) = player {
+            self.reconcile(&snapshot, player);
+        }
+
+        self.snapshots.push(snapshot);
+        if self.snapshots.len() > SNAPSHOT_HISTORY {
+            self.snapshots.remove(0);
+        }
+    }
+
+    /// Restarts the prediction from the server's word on our head and replays
+    /// the inputs it hasn't applied yet on top.
+    fn reconcile(&mut self, snapshot: &Snapshot, player: u8) {
+        let Some(state) = snapshot.snakes.iter().find(|snake| snake.id == player) else {
+            self.prediction = None;
+            return;
+        };
+        if !state.alive {
+            self.prediction = None;
+            return;
+        }
+        let previous = self
+            .prediction
+            .take()
+            .filter(|prediction| prediction.round == snapshot.round);
+        let mut prediction = Prediction {
+            round: snapshot.round,
+            head: Vec2::from(state.head),
+            direction: Vec2::from(state.direction),
+            speed: state.speed,
+            body_length: state.body.len(),
+            path: previous
+                .as_ref()
+                .map_or_else(|| path_from_state(state), |previous| previous.path.clone()),
+        };
+        let tick_length = self.tick_length().as_secs_f32();
+        for (input, _) in self.pending.iter() {
+            predict(
+                &mut prediction,
+                &self.rules,
+                input.steer.into(),
+                tick_length,
+            );
+        }
+        if let Some(previous) = previous {
+            let error = previous.head - prediction.head;
+            if error.length() > 0.01 {
+                self.stats.corrections += 1;
+                self.stats.correction_total += error.length();
+                self.stats.correction_max = self.stats.correction_max.max(error.length());
+                self.correction += error;
+            }
+        } else {
+            self.correction = Vec2::ZERO;
+        }
+        self.prediction = Some(prediction);
+    }
+
+    /// Everything interpolated to just behind the server's clock, with our own
+    /// snake from the prediction instead. `None` until the first snapshot.
+    pub fn frame(&self, now: Instant) -> Option<NetFrame> {
+        let newest = self.snapshots.last()?;
+        let render_tick = self.clock_offset.map_or(newest.tick as f64, |offset| {
+            let elapsed = (now - self.started).as_secs_f64() - offset;
+            (elapsed - self.interpolation_delay.as_secs_f64()) * self.tick_rate as f64
+        });
+        let (from, to, blend) = bracket(&self.snapshots, render_tick);
+        let mut frame = interpolate(from, to, blend);
+
+        if let (Some(player), Some(prediction)) = (self.player(), self.prediction.as_ref()) {
+            if prediction.round == frame.round {
+                let head = prediction.head + self.correction;
+                let mut segments = vec![head];
+                segments.extend((1..=prediction.body_length).filter_map(|index| {
+                    prediction
+                        .path
+                        .sample(index as f32 * self.rules.segment_spacing)
+                        .map(|(position, _)| position + self.correction * 0.5f32.powi(index as i32))
+                }));
+                if let Some(snake) = frame.snakes.iter_mut().find(|snake| snake.id == player) {
+                    snake.segments = segments;
+                    snake.local = true;
+                }
+            }
+        }
+        Some(frame)
+    }
+
+    /// The arena from our snake's point of view, as drawn in `frame`, so a
+    /// built-in brain can play online. `None` while we have no live snake.
+    pub fn view(&self, frame: &NetFrame) -> Option<WorldView> {
+        let prediction = self.prediction.as_ref()?;
+        let own = frame.snakes.iter().find(|snake| snake.local)?;
+        Some(WorldView {
+            head: own.segments[0],
+            heading: prediction.direction,
+            speed: prediction.speed,
+            snake_radius: self.rules.snake_radius,
+            own_body: own.segments[1..].to_vec(),
+            others: frame
+                .snakes
+                .iter()
+                .filter(|snake| !snake.local && snake.alive)
+                .flat_map(|snake| snake.segments.iter().copied())
+                .collect(),
+            coins: frame.coins.clone(),
+            arena_half_size: self.rules.arena_half_size(),
+        })
+    }
+}
+
+impl Drop for Client {
+    /// Frees our seat right away instead of leaving it until the server times us out.
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+fn predict(prediction: &mut Prediction, rules: &Rules, steer: Steer, delta: f32) {
+    prediction.direction = steer_heading(
+        &rules.turning,
+        prediction.head,
+        prediction.direction,
+        prediction.body_length + 1,
+        steer,
+        delta,
+    );
+    prediction.head = advance_head(
+        rules,
+        prediction.head,
+        prediction.direction,
+        prediction.speed,
+        delta,
+    );
+    prediction.path.record(prediction.head);
+    prediction
+        .path
+        .trim((prediction.body_length + 4) as f32 * rules.segment_spacing);
+}
+
+/// A path running back through the snapshot's body, for a fresh prediction.
+fn path_from_state(state: &SnakeState) -> BodyPath {
+    let mut path = BodyPath::default();
+    for point in state.body.iter().rev() {
+        path.record(Vec2::new(point[0] as f32, point[1] as f32));
+    }
+    path.record(Vec2::from(state.head));
+    path
+}
+
+/// The snapshots either side of `tick` in the same round, and how far between
+/// them it is. `snapshots` must be oldest first and not empty.
+pub(super) fn bracket(snapshots: &[Snapshot], tick: f64) -> (&Snapshot, &Snapshot, f32) {
+    let newest = snapshots.last().expect("checked by the caller");
+    let after = snapshots.partition_point(|snapshot| (snapshot.tick as f64) < tick);
+    let Some(to) = snapshots.get(after) else {
+        return (newest, newest, 0.0);
+    };
+    if after == 0 {
+        return (to, to, 0.0);
+    }
+    let from = &snapshots[after - 1];
+    if from.round != to.round {
+        return (to, to, 0.0);
+    }
+    let span = (to.tick - from.tick) as f64;
+    let blend = ((tick - from.tick as f64) / span).clamp(0.0, 1.0);
+    (from, to, blend as f32)
+}
+
+pub(super) fn interpolate(from: &Snapshot, to: &Snapshot, blend: f32) -> NetFrame {
+    // Counts and flags can't be blended, so they come from the nearer snapshot.
+    let nearer = if blend < 0.5 { from } else { to };
+    let point = |point: &[i16; 2]| Vec2::new(point[0] as f32, point[1] as f32);
+    let snakes = to
+        .snakes
+        .iter()
+        .map(|snake| {
+            let mut segments: Vec<Vec2> = std::iter::once(Vec2::from(snake.head))
+                .chain(snake.body.iter().map(point))
+                .collect();
+            if let Some(old) = from.snakes.iter().find(|old| old.id == snake.id) {
+                let old_segments =
+                    std::iter::once(Vec2::from(old.head)).chain(old.body.iter().map(point));
+                for (segment, old) in segments.iter_mut().zip(old_segments) {
+                    *segment = old.lerp(*segment, blend);
+                }
+            }
+            let discrete = nearer
+                .snakes
+                .iter()
+                .find(|near| near.id == snake.id)
+                .unwrap_or(snake);
+            NetSnake {
+                id: snake.id,
+                bot: snake.bot,
+                name: snake.name.clone(),
+                alive: discrete.alive,
+                score: discrete.score,
+                segments,
+                local: false,
+            }
+        })
+        .collect();
+    NetFrame {
+        round: to.round,
+        round_over: nearer.round_over,
+        snakes,
+        coins: nearer.coins.iter().map(point).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(round: u32, tick: u64, x: f32, alive: bool) -> Snapshot {
+        Snapshot {
+            round,
+            tick,
+            ack: 0,
+            round_over: false,
+            snakes: vec![SnakeState {
+                id: 1,
+                bot: true,
+                name: "Bot".to_string(),
+                alive,
+                score: tick as u32,
+                head: [x, 0.0],
+                direction: [1.0, 0.0],
+                speed: 200.0,
+                body: vec![[x as i16 - 10, 0]],
+            }],
+            coins: vec![[tick as i16, 0]],
+        }
+    }
+
+    #[test]
+    fn bracket_finds_the_snapshots_around_a_tick() {
+        let snapshots = [
+            snapshot(1, 10, 0.0, true),
+            snapshot(1, 20, 100.0, true),
+            snapshot(2, 30, 500.0, true),
+        ];
+        let ticks = |(from, to, blend): (&Snapshot, &Snapshot, f32)| (from.tick, to.tick, blend);
+        assert_eq!(ticks(bracket(&snapshots, 15.0)), (10, 20, 0.5));
+        assert_eq!(ticks(bracket(&snapshots, 20.0)), (10, 20, 1.0));
+        // Before the first and after the last snapshot hold still.
+        assert_eq!(ticks(bracket(&snapshots, 2.0)), (10, 10, 0.0));
+        assert_eq!(ticks(bracket(&snapshots, 99.0)), (30, 30, 0.0));
+        // A new round jumps rather than sliding snakes across the arena.
+        assert_eq!(ticks(bracket(&snapshots, 25.0)), (30, 30, 0.0));
+    }
+
+    #[test]
+    fn interpolate_blends_positions_and_picks_the_nearer_counts() {
+        let (from, to) = (snapshot(1, 10, 0.0, true), snapshot(1, 20, 100.0, false));
+        let frame = interpolate(&from, &to, 0.25);
+        let snake = &frame.snakes[0];
+        assert_eq!(snake.segments, [Vec2::new(25.0, 0.0), Vec2::new(15.0, 0.0)]);
+        assert!(snake.alive);
+        assert_eq!(snake.score, 10);
+        assert_eq!(frame.coins, [Vec2::new(10.0, 0.0)]);
+
+        let frame = interpolate(&from, &to, 0.75);
+        assert!(!frame.snakes[0].alive);
+        assert_eq!(frame.coins, [Vec2::new(20.0, 0.0)]);
+    }
+
+    #[test]
+    fn snakes_new_in_the_later_snapshot_are_not_blended() {
+        let mut from = snapshot(1, 10, 0.0, true);
+        from.snakes.clear();
+        let frame = interpolate(&from, &snapshot(1, 20, 100.0, true), 0.25);
+        assert_eq!(frame.snakes[0].segments[0], Vec2::new(100.0, 0.0));
+        assert_eq!(frame.snakes[0].score, 20);
+    }
+}
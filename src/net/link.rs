@@ -0,0 +1,107 @@
+use std::{
+    io,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::protocol::MAX_PACKET_SIZE;
+
+/// Network trouble a [`Link`] adds to everything it sends, for testing over
+/// loopback. Both ends apply their own, so a round trip sees both.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NetConditions {
+    /// One-way delay added to every packet.
+    pub latency: Duration,
+    /// Up to this much extra delay, picked per packet, so packets can arrive out of order.
+    pub jitter: Duration,
+    /// Share of packets dropped, from 0 to 1.
+    pub loss: f32,
+}
+
+/// Datagrams sent and received by one end.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LinkStats {
+    pub sent: u64,
+    /// Packets the simulated conditions threw away.
+    pub dropped: u64,
+    pub received: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+/// A non-blocking UDP socket that holds back or drops outgoing packets
+/// according to its [`NetConditions`].
+pub struct Link {
+    socket: UdpSocket,
+    pub conditions: NetConditions,
+    /// Packets waiting out their simulated delay, with when they're due.
+    delayed: Vec<(Instant, SocketAddr, Vec<u8>)>,
+    rng: StdRng,
+    buffer: Vec<u8>,
+    pub stats: LinkStats,
+}
+
+impl Link {
+    pub fn bind(address: impl ToSocketAddrs, conditions: NetConditions) -> io::Result<Link> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        Ok(Link {
+            socket,
+            conditions,
+            delayed: Vec::new(),
+            rng: StdRng::from_entropy(),
+            buffer: vec![0; MAX_PACKET_SIZE],
+            stats: LinkStats::default(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    pub fn send(&mut self, to: SocketAddr, packet: Vec<u8>, now: Instant) {
+        if self.conditions.loss > 0.0 && self.rng.gen::<f32>() < self.conditions.loss {
+            self.stats.dropped += 1;
+            return;
+        }
+        let jitter = self.conditions.jitter.mul_f32(self.rng.gen());
+        let due = now + self.conditions.latency + jitter;
+        self.delayed.push((due, to, packet));
+        self.flush(now);
+    }
+
+    /// Sends every packet whose delay is up. Call it often, even with nothing new to send.
+    pub fn flush(&mut self, now: Instant) {
+        let mut index = 0;
+        while index < self.delayed.len() {
+            if self.delayed[index].0 > now {
+                index += 1;
+                continue;
+            }
+            let (_, to, packet) = self.delayed.swap_remove(index);
+            // A full send buffer or an unreachable peer is just more packet loss.
+            if self.socket.send_to(&packet, to).is_ok() {
+                self.stats.sent += 1;
+                self.stats.bytes_sent += packet.len() as u64;
+            }
+        }
+    }
+
+    /// Next datagram waiting on the socket, if any.
+    pub fn receive(&mut self) -> Option<(SocketAddr, &[u8])> {
+        loop {
+            match self.socket.recv_from(&mut self.buffer) {
+                Ok((length, from)) => {
+                    self.stats.received += 1;
+                    self.stats.bytes_received += length as u64;
+                    return Some((from, &self.buffer[..length]));
+                }
+                // Windows reports an earlier send to a closed port on the next receive.
+                Err(error) if error.kind() == io::ErrorKind::ConnectionReset => continue,
+                Err(_) => return None,
+            }
+        }
+    }
+}
@@ -0,0 +1,311 @@
+//! Packets exchanged between the server and its clients, one JSON object per
+//! UDP datagram.
+//!
+//! A client says [`ClientPacket::Hello`] until it gets a [`ServerPacket::Welcome`]
+//! with its player id, then sends its steering every tick as numbered
+//! [`InputFrame`]s. Each packet repeats every frame the server hasn't
+//! acknowledged yet, so a lost packet costs nothing as long as a later one
+//! arrives. The server answers with a [`Snapshot`] of the whole arena every few
+//! ticks, carrying the newest input it has applied for that client.
+//!
+//! A spectator says hello with `spectate` set and is welcomed without a player
+//! id. It never sends inputs, only a [`ClientPacket::KeepAlive`] now and then,
+//! and gets the same snapshots as the players.
+
+use bevy::math::Vec2;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::sim::{Difficulty, Steer};
+
+/// Bumped whenever a packet changes shape. Servers turn away clients on another version.
+pub const PROTOCOL_VERSION: u16 = 2;
+
+/// Largest datagram either side reads.
+pub const MAX_PACKET_SIZE: usize = 65_507;
+
+/// Longest player name a server keeps, in characters. Every snapshot carries
+/// the names, so longer ones are cut rather than crowding out the snakes.
+pub const MAX_NAME_LENGTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientPacket {
+    Hello {
+        version: u16,
+        name: String,
+        /// Watch without a snake.
+        #[serde(default)]
+        spectate: bool,
+    },
+    Input {
+        /// Oldest first.
+        inputs: Vec<InputFrame>,
+    },
+    /// Sent by spectators, which have no inputs to show they're still there.
+    KeepAlive,
+    Bye,
+}
+
+/// Steering for one server tick.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct InputFrame {
+    pub seq: u32,
+    pub steer: NetSteer,
+}
+
+/// [`Steer`] in a form that goes over the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NetSteer {
+    Hold,
+    Toward { x: f32, y: f32 },
+    Turn { share: f32 },
+}
+
+impl From<Steer> for NetSteer {
+    fn from(steer: Steer) -> NetSteer {
+        match steer {
+            Steer::Hold => NetSteer::Hold,
+            Steer::Toward(target) => NetSteer::Toward {
+                x: target.x,
+                y: target.y,
+            },
+            Steer::Turn(share) => NetSteer::Turn { share },
+        }
+    }
+}
+
+impl From<NetSteer> for Steer {
+    fn from(steer: NetSteer) -> Steer {
+        match steer {
+            NetSteer::Hold => Steer::Hold,
+            NetSteer::Toward { x, y } => Steer::Toward(Vec2::new(x, y)),
+            NetSteer::Turn { share } => Steer::Turn(share),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerPacket {
+    Welcome {
+        version: u16,
+        /// Id of the client's snake in snapshots. `None` for spectators.
+        player: Option<u8>,
+        /// Server ticks per second. Clients send one input per tick.
+        tick_rate: u32,
+        /// Ticks between snapshots.
+        snapshot_interval: u32,
+        difficulty: Difficulty,
+    },
+    Reject {
+        reason: String,
+    },
+    Snapshot(Snapshot),
+}
+
+/// The whole arena after a server tick.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Counts up from 1; a new round starts with fresh snakes.
+    pub round: u32,
+    /// Server ticks since it started, across rounds.
+    pub tick: u64,
+    /// Newest input from the receiving client applied by this tick. Always 0
+    /// for spectators and in replays.
+    pub ack: u32,
+    /// The round has ended and the next one starts shortly.
+    pub round_over: bool,
+    pub snakes: Vec<SnakeState>,
+    /// Rounded to whole world units.
+    pub coins: Vec<[i16; 2]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnakeState {
+    /// Player id; bots are numbered after the player seats.
+    pub id: u8,
+    pub bot: bool,
+    pub name: String,
+    pub alive: bool,
+    pub score: u32,
+    /// Exact, since the owner's prediction starts from it.
+    pub head: [f32; 2],
+    pub direction: [f32; 2],
+    pub speed: f32,
+    /// Segments after the head, rounded to whole world units to keep snapshots small.
+    pub body: Vec<[i16; 2]>,
+}
+
+pub fn quantize(point: Vec2) -> [i16; 2] {
+    [point.x.round() as i16, point.y.round() as i16]
+}
+
+pub fn encode<T: Serialize>(packet: &T) -> Vec<u8> {
+    serde_json::to_vec(packet).expect("packets always serialize")
+}
+
+/// Encodes a packet that may not fit in one datagram. A snapshot that's too
+/// big loses the tail ends of its longest bodies, then coins, until it fits,
+/// since a datagram over [`MAX_PACKET_SIZE`] would never arrive at all.
+/// Returns the packet as sent and how many points were cut from it.
+pub fn encode_to_fit(packet: &mut ServerPacket) -> (Vec<u8>, usize) {
+    // Points take at most this many bytes each, as in `[-32768,-32768],`, so
+    // cutting by it never drops more than needed.
+    const MAX_POINT_SIZE: usize = 16;
+    let mut cut = 0;
+    loop {
+        let bytes = encode(packet);
+        let ServerPacket::Snapshot(snapshot) = packet else {
+            return (bytes, cut);
+        };
+        if bytes.len() <= MAX_PACKET_SIZE {
+            return (bytes, cut);
+        }
+        let excess = (bytes.len() - MAX_PACKET_SIZE).div_ceil(MAX_POINT_SIZE);
+        let longest = snapshot
+            .snakes
+            .iter_mut()
+            .map(|snake| &mut snake.body)
+            .chain([&mut snapshot.coins])
+            .max_by_key(|points| points.len());
+        match longest {
+            Some(points) if !points.is_empty() => {
+                let keep = points.len().saturating_sub(excess);
+                cut += points.len() - keep;
+                points.truncate(keep);
+            }
+            _ => return (bytes, cut),
+        }
+    }
+}
+
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Option<T> {
+    serde_json::from_slice(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(bodies: &[usize], coins: usize) -> Snapshot {
+        Snapshot {
+            round: 1,
+            tick: 40,
+            ack: 7,
+            round_over: false,
+            snakes: bodies
+                .iter()
+                .enumerate()
+                .map(|(id, length)| SnakeState {
+                    id: id as u8,
+                    bot: false,
+                    name: format!("snake {id}"),
+                    alive: true,
+                    score: 0,
+                    head: [0.5, -2.25],
+                    direction: [1.0, 0.0],
+                    speed: 200.0,
+                    body: vec![[-1200, 800]; *length],
+                })
+                .collect(),
+            coins: vec![[310, -42]; coins],
+        }
+    }
+
+    #[test]
+    fn packets_survive_a_round_trip() {
+        let client = [
+            ClientPacket::Hello {
+                version: PROTOCOL_VERSION,
+                name: "Alice".to_string(),
+                spectate: true,
+            },
+            ClientPacket::Input {
+                inputs: vec![
+                    InputFrame {
+                        seq: 3,
+                        steer: Steer::Toward(Vec2::new(1.5, -2.0)).into(),
+                    },
+                    InputFrame {
+                        seq: 4,
+                        steer: Steer::Turn(-0.5).into(),
+                    },
+                ],
+            },
+            ClientPacket::KeepAlive,
+            ClientPacket::Bye,
+        ];
+        for packet in client {
+            assert_eq!(decode::<ClientPacket>(&encode(&packet)), Some(packet));
+        }
+        let server = [
+            ServerPacket::Welcome {
+                version: PROTOCOL_VERSION,
+                player: None,
+                tick_rate: 60,
+                snapshot_interval: 3,
+                difficulty: Difficulty::Hard,
+            },
+            ServerPacket::Reject {
+                reason: "full".to_string(),
+            },
+            ServerPacket::Snapshot(snapshot(&[3, 0], 2)),
+        ];
+        for packet in server {
+            assert_eq!(decode::<ServerPacket>(&encode(&packet)), Some(packet));
+        }
+    }
+
+    #[test]
+    fn hello_without_spectate_is_a_player_and_garbage_is_dropped() {
+        let hello = br#"{"type":"hello","version":2,"name":"Bob"}"#;
+        assert_eq!(
+            decode::<ClientPacket>(hello),
+            Some(ClientPacket::Hello {
+                version: 2,
+                name: "Bob".to_string(),
+                spectate: false,
+            })
+        );
+        assert_eq!(decode::<ClientPacket>(b"{\"type\":\"dance\"}"), None);
+        assert_eq!(decode::<ServerPacket>(b"\xff\x00"), None);
+    }
+
+    #[test]
+    fn steering_converts_both_ways() {
+        for steer in [
+            Steer::Hold,
+            Steer::Toward(Vec2::new(3.0, 4.0)),
+            Steer::Turn(0.25),
+        ] {
+            assert_eq!(Steer::from(NetSteer::from(steer)), steer);
+        }
+    }
+
+    #[test]
+    fn small_packets_are_sent_as_they_are() {
+        let mut packet = ServerPacket::Snapshot(snapshot(&[10, 20], 5));
+        let before = packet.clone();
+        assert_eq!(encode_to_fit(&mut packet), (encode(&before), 0));
+        assert_eq!(packet, before);
+    }
+
+    #[test]
+    fn oversized_snapshots_lose_their_longest_bodies_first() {
+        let mut packet = ServerPacket::Snapshot(snapshot(&[9000, 50], 100));
+        assert!(encode(&packet).len() > MAX_PACKET_SIZE);
+        let (bytes, cut) = encode_to_fit(&mut packet);
+        assert!(bytes.len() <= MAX_PACKET_SIZE);
+        assert_eq!(decode::<ServerPacket>(&bytes).as_ref(), Some(&packet));
+
+        let ServerPacket::Snapshot(snapshot) = packet else {
+            unreachable!();
+        };
+        assert_eq!(cut, 9000 - snapshot.snakes[0].body.len());
+        // Each of these points takes 12 bytes, so little more than the excess goes.
+        assert!(snapshot.snakes[0].body.len() > 5000);
+        assert_eq!(snapshot.snakes[1].body.len(), 50);
+        assert_eq!(snapshot.coins.len(), 100);
+    }
+}
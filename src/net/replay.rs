@@ -0,0 +1,236 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    client::{bracket, interpolate, NetFrame},
+    protocol::{Snapshot, PROTOCOL_VERSION},
+};
+use crate::sim::Difficulty;
+
+/// First line of a replay file. Every line after it is one [`Snapshot`], in
+/// the order the server sent them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayHeader {
+    /// The [`PROTOCOL_VERSION`] the snapshots were written in.
+    pub version: u16,
+    pub tick_rate: u32,
+    pub snapshot_interval: u32,
+    pub difficulty: Difficulty,
+}
+
+/// Appends snapshots to a replay file as the server sends them. Every line is
+/// flushed at once, so a replay can be watched while it's still being written
+/// and survives the server being killed.
+pub(super) struct ReplayWriter {
+    file: BufWriter<File>,
+}
+
+impl ReplayWriter {
+    pub fn create(path: &Path, header: &ReplayHeader) -> io::Result<ReplayWriter> {
+        let mut writer = ReplayWriter {
+            file: BufWriter::new(File::create(path)?),
+        };
+        writer.write_line(header)?;
+        Ok(writer)
+    }
+
+    pub fn write(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        self.write_line(snapshot)
+    }
+
+    fn write_line<T: Serialize>(&mut self, line: &T) -> io::Result<()> {
+        serde_json::to_writer(&mut self.file, line)?;
+        self.file.write_all(b"\n")?;
+        self.file.flush()
+    }
+}
+
+/// A recorded session, drawn at any point in its timeline the same way a
+/// client draws live snapshots.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub header: ReplayHeader,
+    /// Oldest first. Never empty.
+    pub snapshots: Vec<Snapshot>,
+}
+
+impl Replay {
+    /// Reads a file written by a server started with a recording path. A
+    /// half-written last line, from a server that was stopped mid-write, is
+    /// dropped.
+    pub fn load(path: &Path) -> io::Result<Replay> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut lines = BufReader::new(File::open(path)?).lines();
+        let header: ReplayHeader = match lines.next() {
+            Some(line) => serde_json::from_str(&line?)
+                .map_err(|error| invalid(format!("bad replay header: {error}")))?,
+            None => return Err(invalid("empty replay".to_string())),
+        };
+        if header.version != PROTOCOL_VERSION {
+            return Err(invalid(format!(
+                "replay is from protocol {}, this build reads {PROTOCOL_VERSION}",
+                header.version
+            )));
+        }
+        let lines: Vec<String> = lines.collect::<io::Result<_>>()?;
+        let mut snapshots = Vec::with_capacity(lines.len());
+        for (index, line) in lines.iter().enumerate() {
+            match serde_json::from_str(line) {
+                Ok(snapshot) => snapshots.push(snapshot),
+                Err(_) if index + 1 == lines.len() => {}
+                Err(error) => {
+                    return Err(invalid(format!(
+                        "bad snapshot on line {}: {error}",
+                        index + 2
+                    )))
+                }
+            }
+        }
+        if snapshots.is_empty() {
+            return Err(invalid("replay has no snapshots".to_string()));
+        }
+        Ok(Replay { header, snapshots })
+    }
+
+    /// Seconds from the first snapshot to the last.
+    pub fn duration(&self) -> f64 {
+        self.seconds_at(self.snapshots.len() - 1)
+    }
+
+    /// The arena `seconds` into the replay, interpolated between snapshots.
+    pub fn frame(&self, seconds: f64) -> NetFrame {
+        let tick = self.snapshots[0].tick as f64 + seconds * self.header.tick_rate.max(1) as f64;
+        let (from, to, blend) = bracket(&self.snapshots, tick);
+        interpolate(from, to, blend)
+    }
+
+    /// Start times of every round, in seconds, for skipping between them.
+    pub fn round_starts(&self) -> Vec<f64> {
+        let mut starts = vec![0.0];
+        for index in 1..self.snapshots.len() {
+            if self.snapshots[index].round != self.snapshots[index - 1].round {
+                starts.push(self.seconds_at(index));
+            }
+        }
+        starts
+    }
+
+    fn seconds_at(&self, index: usize) -> f64 {
+        (self.snapshots[index].tick - self.snapshots[0].tick) as f64
+            / self.header.tick_rate.max(1) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+
+    fn header() -> ReplayHeader {
+        ReplayHeader {
+            version: PROTOCOL_VERSION,
+            tick_rate: 60,
+            snapshot_interval: 3,
+            difficulty: Difficulty::Normal,
+        }
+    }
+
+    fn snapshot(round: u32, tick: u64) -> Snapshot {
+        Snapshot {
+            round,
+            tick,
+            ack: 0,
+            round_over: false,
+            snakes: Vec::new(),
+            coins: vec![[tick as i16, 0]],
+        }
+    }
+
+    /// A replay file named after the test, removed again when dropped.
+    struct TempReplay(PathBuf);
+
+    impl TempReplay {
+        fn new(name: &str) -> TempReplay {
+            let path = std::env::temp_dir()
+                .join(format!("bevy-snake-{name}-{}.replay", std::process::id()));
+            TempReplay(path)
+        }
+
+        fn write(&self, snapshots: &[Snapshot], tail: &str) {
+            let mut writer = ReplayWriter::create(&self.0, &header()).unwrap();
+            for snapshot in snapshots {
+                writer.write(snapshot).unwrap();
+            }
+            drop(writer);
+            let mut file = fs::OpenOptions::new().append(true).open(&self.0).unwrap();
+            file.write_all(tail.as_bytes()).unwrap();
+        }
+    }
+
+    impl Drop for TempReplay {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn loads_what_the_writer_wrote() {
+        let file = TempReplay::new("round-trip");
+        let snapshots = [snapshot(1, 0), snapshot(1, 60), snapshot(2, 90)];
+        file.write(&snapshots, "");
+        let replay = Replay::load(&file.0).unwrap();
+        assert_eq!(replay.header, header());
+        assert_eq!(replay.snapshots, snapshots);
+        assert_eq!(replay.duration(), 1.5);
+        assert_eq!(replay.round_starts(), [0.0, 1.5]);
+    }
+
+    #[test]
+    fn a_half_written_last_line_is_dropped() {
+        let file = TempReplay::new("truncated");
+        file.write(
+            &[snapshot(1, 0), snapshot(1, 3)],
+            r#"{"round":1,"tick":6,"ac"#,
+        );
+        let replay = Replay::load(&file.0).unwrap();
+        assert_eq!(replay.snapshots.len(), 2);
+    }
+
+    #[test]
+    fn broken_lines_before_the_end_are_errors() {
+        let file = TempReplay::new("corrupt");
+        file.write(&[snapshot(1, 0)], "garbage\n{\"round\":1}\n");
+        let error = Replay::load(&file.0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("line 3"), "{error}");
+    }
+
+    #[test]
+    fn other_versions_and_empty_replays_are_refused() {
+        let file = TempReplay::new("empty");
+        file.write(&[], "{\"round\":1,");
+        assert!(Replay::load(&file.0).is_err());
+
+        let old = ReplayHeader {
+            version: PROTOCOL_VERSION - 1,
+            ..header()
+        };
+        fs::write(
+            &file.0,
+            format!(
+                "{}\n{}\n",
+                serde_json::to_string(&old).unwrap(),
+                serde_json::to_string(&snapshot(1, 0)).unwrap()
+            ),
+        )
+        .unwrap();
+        let error = Replay::load(&file.0).unwrap_err();
+        assert!(error.to_string().contains("protocol"), "{error}");
+    }
+}
@@ -0,0 +1,740 @@
+use std::{
+    collections::VecDeque,
+    io,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use bevy::log::warn;
+
+use super::{
+    link::{Link, NetConditions},
+    protocol::{
+        decode, encode, encode_to_fit, quantize, ClientPacket, InputFrame, ServerPacket,
+        SnakeState, Snapshot, MAX_NAME_LENGTH, MAX_PACKET_SIZE, PROTOCOL_VERSION,
+    },
+    replay::{ReplayHeader, ReplayWriter},
+};
+use crate::sim::{BrainKind, Difficulty, Rules, Simulation, SnakeBrain, Steer};
+
+/// Inputs a client may run ahead of the server before the oldest are skipped,
+/// so a client whose clock runs fast doesn't build up lag.
+const MAX_BUFFERED_INPUTS: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub bind: String,
+    /// Player seats. Clients beyond this are turned away.
+    pub players: usize,
+    /// Spectators allowed at once, on top of the players.
+    pub spectators: usize,
+    /// Bots added to every round.
+    pub bots: usize,
+    pub bot_brain: BrainKind,
+    pub difficulty: Difficulty,
+    /// Ticks per second.
+    pub tick_rate: u32,
+    /// Ticks between snapshots.
+    pub snapshot_interval: u32,
+    /// Seconds between the end of a round and the start of the next.
+    pub round_delay: f32,
+    /// Clients silent for this long lose their seat.
+    pub timeout: Duration,
+    pub conditions: NetConditions,
+    /// Writes every snapshot to this file, for watching as a replay.
+    pub record: Option<PathBuf>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: "127.0.0.1:5000".to_string(),
+            players: 2,
+            spectators: 8,
+            bots: 0,
+            bot_brain: BrainKind::Cautious,
+            difficulty: Difficulty::Normal,
+            tick_rate: 60,
+            snapshot_interval: 2,
+            round_delay: 3.0,
+            timeout: Duration::from_secs(5),
+            conditions: NetConditions::default(),
+            record: None,
+        }
+    }
+}
+
+struct Connection {
+    address: SocketAddr,
+    name: String,
+    /// Inputs received but not yet applied, in order.
+    inputs: VecDeque<InputFrame>,
+    /// Newest input applied, acknowledged in every snapshot.
+    applied: u32,
+    /// Repeated on ticks with no new input.
+    steer: Steer,
+    last_heard: Instant,
+}
+
+/// Someone watching without a snake.
+struct Spectator {
+    address: SocketAddr,
+    last_heard: Instant,
+}
+
+/// Who steers a snake in the current round.
+enum Seat {
+    Player(u8),
+    Bot(Box<dyn SnakeBrain>),
+}
+
+/// Runs the one true copy of the game. Clients only send steering and draw
+/// what the snapshots show.
+pub struct Server {
+    pub config: ServerConfig,
+    link: Link,
+    rules: Rules,
+    /// Indexed by player id.
+    connections: Vec<Option<Connection>>,
+    spectators: Vec<Spectator>,
+    recorder: Option<ReplayWriter>,
+    simulation: Simulation,
+    /// One per snake in `simulation`.
+    seats: Vec<Seat>,
+    round: u32,
+    tick: u64,
+    /// Simulated time the current round ended at.
+    round_over_at: Option<f32>,
+    /// Last round a snapshot had to be cut down to fit, so it's reported once.
+    trimmed_round: u32,
+}
+
+impl Server {
+    pub fn bind(config: ServerConfig) -> io::Result<Server> {
+        let link = Link::bind(config.bind.as_str(), config.conditions)?;
+        let rules = Rules::for_difficulty(config.difficulty);
+        let recorder = match &config.record {
+            Some(path) => Some(ReplayWriter::create(
+                path,
+                &ReplayHeader {
+                    version: PROTOCOL_VERSION,
+                    tick_rate: config.tick_rate,
+                    snapshot_interval: config.snapshot_interval,
+                    difficulty: config.difficulty,
+                },
+            )?),
+            None => None,
+        };
+        Ok(Server {
+            link,
+            simulation: Simulation::new(rules.clone(), 0, &[]),
+            rules,
+            connections: (0..config.players).map(|_| None).collect(),
+            spectators: Vec::new(),
+            recorder,
+            seats: Vec::new(),
+            round: 0,
+            tick: 0,
+            round_over_at: None,
+            trimmed_round: 0,
+            config,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.link.local_addr()
+    }
+
+    /// Ticks at the configured rate until `running` is cleared. Only fails if
+    /// the recording can't be written.
+    pub fn run(&mut self, running: &AtomicBool) -> io::Result<()> {
+        let tick_length = Duration::from_secs_f64(1.0 / self.config.tick_rate as f64);
+        let mut next_tick = Instant::now();
+        while running.load(Ordering::Relaxed) {
+            let now = Instant::now();
+            self.receive(now);
+            if now >= next_tick {
+                self.tick(now)?;
+                next_tick += tick_length;
+                // Don't try to catch up after a stall; just carry on from here.
+                if now > next_tick + tick_length * 5 {
+                    next_tick = now + tick_length;
+                }
+            }
+            self.link.flush(now);
+            thread::sleep(Duration::from_millis(1).min(next_tick.saturating_duration_since(now)));
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self, now: Instant) {
+        while let Some((from, packet)) = self
+            .link
+            .receive()
+            .map(|(from, bytes)| (from, decode::<ClientPacket>(bytes)))
+        {
+            let Some(packet) = packet else {
+                continue;
+            };
+            match packet {
+                ClientPacket::Hello {
+                    version,
+                    name,
+                    spectate,
+                } => self.greet(from, version, name, spectate, now),
+                ClientPacket::KeepAlive => {
+                    if let Some(spectator) = self
+                        .spectators
+                        .iter_mut()
+                        .find(|spectator| spectator.address == from)
+                    {
+                        spectator.last_heard = now;
+                    }
+                }
+                ClientPacket::Input { inputs } => {
+                    let Some(connection) = self.connection_mut(from) else {
+                        continue;
+                    };
+                    connection.last_heard = now;
+                    let newest = connection
+                        .inputs
+                        .back()
+                        .map_or(connection.applied, |input| input.seq);
+                    // Packets repeat unacknowledged inputs and can arrive out of order.
+                    connection
+                        .inputs
+                        .extend(inputs.into_iter().filter(|input| input.seq > newest));
+                    while connection.inputs.len() > MAX_BUFFERED_INPUTS {
+                        connection.inputs.pop_front();
+                    }
+                }
+                ClientPacket::Bye => {
+                    if let Some(slot) = self.slot(from) {
+                        self.connections[slot] = None;
+                    }
+                    self.spectators
+                        .retain(|spectator| spectator.address != from);
+                }
+            }
+        }
+    }
+
+    fn greet(
+        &mut self,
+        from: SocketAddr,
+        version: u16,
+        name: String,
+        spectate: bool,
+        now: Instant,
+    ) {
+        if version != PROTOCOL_VERSION {
+            let reason =
+                format!("server speaks protocol {PROTOCOL_VERSION}, client speaks {version}");
+            self.send(from, &ServerPacket::Reject { reason }, now);
+            return;
+        }
+        if spectate {
+            self.greet_spectator(from, now);
+            return;
+        }
+        // A repeated hello means our welcome got lost.
+        let slot = match self.slot(from) {
+            Some(slot) => slot,
+            None => {
+                let Some(slot) = self.connections.iter().position(Option::is_none) else {
+                    let reason = "server is full".to_string();
+                    self.send(from, &ServerPacket::Reject { reason }, now);
+                    return;
+                };
+                self.connections[slot] = Some(Connection {
+                    address: from,
+                    name: name.chars().take(MAX_NAME_LENGTH).collect(),
+                    inputs: VecDeque::new(),
+                    applied: 0,
+                    steer: Steer::Hold,
+                    last_heard: now,
+                });
+                slot
+            }
+        };
+        let welcome = ServerPacket::Welcome {
+            version: PROTOCOL_VERSION,
+            player: Some(slot as u8),
+            tick_rate: self.config.tick_rate,
+            snapshot_interval: self.config.snapshot_interval,
+            difficulty: self.config.difficulty,
+        };
+        self.send(from, &welcome, now);
+    }
+
+    fn greet_spectator(&mut self, from: SocketAddr, now: Instant) {
+        if self.slot(from).is_some() {
+            let reason = "already playing from this address".to_string();
+            self.send(from, &ServerPacket::Reject { reason }, now);
+            return;
+        }
+        let room = self.spectators.len() < self.config.spectators;
+        match self
+            .spectators
+            .iter_mut()
+            .find(|spectator| spectator.address == from)
+        {
+            Some(spectator) => spectator.last_heard = now,
+            None if room => self.spectators.push(Spectator {
+                address: from,
+                last_heard: now,
+            }),
+            None => {
+                let reason = "no room for more spectators".to_string();
+                self.send(from, &ServerPacket::Reject { reason }, now);
+                return;
+            }
+        }
+        let welcome = ServerPacket::Welcome {
+            version: PROTOCOL_VERSION,
+            player: None,
+            tick_rate: self.config.tick_rate,
+            snapshot_interval: self.config.snapshot_interval,
+            difficulty: self.config.difficulty,
+        };
+        self.send(from, &welcome, now);
+    }
+
+    fn slot(&self, address: SocketAddr) -> Option<usize> {
+        self.connections.iter().position(|connection| {
+            connection
+                .as_ref()
+                .is_some_and(|connection| connection.address == address)
+        })
+    }
+
+    fn connection_mut(&mut self, address: SocketAddr) -> Option<&mut Connection> {
+        let slot = self.slot(address)?;
+        self.connections[slot].as_mut()
+    }
+
+    fn send(&mut self, to: SocketAddr, packet: &ServerPacket, now: Instant) {
+        self.link.send(to, encode(packet), now);
+    }
+
+    fn tick(&mut self, now: Instant) -> io::Result<()> {
+        self.tick += 1;
+        for connection in self.connections.iter_mut() {
+            if connection
+                .as_ref()
+                .is_some_and(|connection| now - connection.last_heard > self.config.timeout)
+            {
+                *connection = None;
+            }
+        }
+        self.spectators
+            .retain(|spectator| now - spectator.last_heard <= self.config.timeout);
+
+        let delta = 1.0 / self.config.tick_rate as f32;
+        match self.round_over_at {
+            _ if self.seats.is_empty() => self.start_round(),
+            Some(ended) if self.simulation.time - ended >= self.config.round_delay => {
+                self.start_round()
+            }
+            // Time keeps running between rounds so the delay can elapse.
+            Some(_) => self.simulation.time += delta,
+            None => {
+                let steering = self.steering();
+                self.simulation.step(delta, &steering);
+                if self.round_is_over() {
+                    self.round_over_at = Some(self.simulation.time);
+                }
+            }
+        }
+
+        if self
+            .tick
+            .is_multiple_of(self.config.snapshot_interval.max(1) as u64)
+        {
+            self.send_snapshots(now)?;
+        }
+        Ok(())
+    }
+
+    /// Seats every connected player plus the bots, once anyone is connected.
+    fn start_round(&mut self) {
+        let players: Vec<u8> = self
+            .connections
+            .iter()
+            .enumerate()
+            .filter(|(_, connection)| connection.is_some())
+            .map(|(slot, _)| slot as u8)
+            .collect();
+        if players.is_empty() {
+            self.seats.clear();
+            return;
+        }
+        self.seats = players
+            .into_iter()
+            .map(Seat::Player)
+            .chain((0..self.config.bots).map(|_| Seat::Bot(self.config.bot_brain.brain())))
+            .collect();
+        self.round += 1;
+        self.simulation = Simulation::new(
+            self.rules.clone(),
+            self.round as u64,
+            &Simulation::ring_spawns(self.seats.len()),
+        );
+        self.round_over_at = None;
+    }
+
+    fn steering(&mut self) -> Vec<Steer> {
+        let mut steering = Vec::with_capacity(self.seats.len());
+        for (index, seat) in self.seats.iter_mut().enumerate() {
+            let steer = match seat {
+                Seat::Player(slot) => match self.connections[*slot as usize].as_mut() {
+                    Some(connection) => {
+                        if let Some(input) = connection.inputs.pop_front() {
+                            connection.applied = input.seq;
+                            connection.steer = input.steer.into();
+                        }
+                        connection.steer
+                    }
+                    None => Steer::Hold,
+                },
+                Seat::Bot(brain) if self.simulation.snakes[index].is_alive() => brain
+                    .steer(&self.simulation.view(index))
+                    .map_or(Steer::Hold, Steer::Toward),
+                Seat::Bot(_) => Steer::Hold,
+            };
+            steering.push(steer);
+        }
+        steering
+    }
+
+    /// Like the local game, bots never decide a round: it ends when the last
+    /// player is out, or with one left when several started. Players who have
+    /// left count as out.
+    fn round_is_over(&self) -> bool {
+        let mut players = 0;
+        let mut alive = 0;
+        for (seat, snake) in self.seats.iter().zip(self.simulation.snakes.iter()) {
+            if let Seat::Player(slot) = seat {
+                players += 1;
+                if snake.is_alive() && self.connections[*slot as usize].is_some() {
+                    alive += 1;
+                }
+            }
+        }
+        if players > 1 {
+            alive <= 1
+        } else {
+            alive == 0
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let snakes = self
+            .seats
+            .iter()
+            .zip(self.simulation.snakes.iter())
+            .enumerate()
+            .map(|(index, (seat, snake))| {
+                let (id, bot, name) = match seat {
+                    Seat::Player(slot) => (
+                        *slot,
+                        false,
+                        self.connections[*slot as usize]
+                            .as_ref()
+                            .map_or_else(|| "Gone".to_string(), |c| c.name.clone()),
+                    ),
+                    Seat::Bot(_) => {
+                        let number = index - self.player_count() + 1;
+                        (
+                            (self.config.players + number - 1) as u8,
+                            true,
+                            format!("Bot {number}"),
+                        )
+                    }
+                };
+                SnakeState {
+                    id,
+                    bot,
+                    name,
+                    alive: snake.is_alive(),
+                    score: snake.score,
+                    head: snake.head().to_array(),
+                    direction: snake.direction.to_array(),
+                    speed: snake.speed,
+                    body: snake
+                        .segments
+                        .iter()
+                        .skip(1)
+                        .map(|segment| quantize(segment.position))
+                        .collect(),
+                }
+            })
+            .collect();
+        Snapshot {
+            round: self.round,
+            tick: self.tick,
+            ack: 0,
+            round_over: self.round_over_at.is_some(),
+            snakes,
+            coins: self
+                .simulation
+                .coins
+                .iter()
+                .map(|coin| quantize(*coin))
+                .collect(),
+        }
+    }
+
+    fn player_count(&self) -> usize {
+        self.seats
+            .iter()
+            .filter(|seat| matches!(seat, Seat::Player(_)))
+            .count()
+    }
+
+    fn send_snapshots(&mut self, now: Instant) -> io::Result<()> {
+        if self.seats.is_empty() {
+            return Ok(());
+        }
+        let snapshot = self.snapshot();
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.write(&snapshot)?;
+        }
+        // Fitted once with the widest possible ack, so every recipient's copy fits.
+        let mut packet = ServerPacket::Snapshot(Snapshot {
+            ack: u32::MAX,
+            ..snapshot
+        });
+        let (_, cut) = encode_to_fit(&mut packet);
+        if cut > 0 && self.trimmed_round != self.round {
+            self.trimmed_round = self.round;
+            warn!(
+                "Snapshot for tick {} is over {MAX_PACKET_SIZE} bytes; cut {cut} body and coin points to fit",
+                self.tick
+            );
+        }
+        let recipients: Vec<(SocketAddr, u32)> = self
+            .connections
+            .iter()
+            .flatten()
+            .map(|connection| (connection.address, connection.applied))
+            .chain(
+                self.spectators
+                    .iter()
+                    .map(|spectator| (spectator.address, 0)),
+            )
+            .collect();
+        for (address, applied) in recipients {
+            if let ServerPacket::Snapshot(snapshot) = &mut packet {
+                snapshot.ack = applied;
+            }
+            self.send(address, &packet, now);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::UdpSocket;
+
+    use bevy::math::Vec2;
+
+    use super::*;
+    use crate::net::{Client, ClientState};
+
+    fn server(conditions: NetConditions) -> Server {
+        Server::bind(ServerConfig {
+            bind: "127.0.0.1:0".to_string(),
+            players: 1,
+            conditions,
+            ..ServerConfig::default()
+        })
+        .unwrap()
+    }
+
+    /// Plays server and clients on this thread in real time, the way
+    /// [`Server::run`] paces ticks, calling `each_tick` before every tick.
+    fn play(
+        server: &mut Server,
+        clients: &mut [Client],
+        length: Duration,
+        mut each_tick: impl FnMut(&mut Server),
+    ) {
+        let tick_length = Duration::from_secs_f64(1.0 / server.config.tick_rate as f64);
+        let started = Instant::now();
+        let mut next_tick = started;
+        while started.elapsed() < length {
+            let now = Instant::now();
+            server.receive(now);
+            if now >= next_tick {
+                each_tick(server);
+                server.tick(now).unwrap();
+                next_tick += tick_length;
+            }
+            server.link.flush(now);
+            for client in clients.iter_mut() {
+                client.update(now, Steer::Hold);
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Sends `hello` from a fresh socket and waits for the server's reply.
+    fn say_hello(server: &mut Server, hello: &ClientPacket) -> Option<ServerPacket> {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket
+            .send_to(&encode(hello), server.local_addr().unwrap())
+            .unwrap();
+
+        let give_up = Instant::now() + Duration::from_secs(2);
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        socket.set_nonblocking(true).unwrap();
+        loop {
+            assert!(Instant::now() < give_up, "no reply");
+            let now = Instant::now();
+            server.receive(now);
+            server.link.flush(now);
+            if let Ok((length, _)) = socket.recv_from(&mut buffer) {
+                break decode::<ServerPacket>(&buffer[..length]);
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn clients_on_another_version_are_turned_away() {
+        let mut server = server(NetConditions::default());
+        let hello = ClientPacket::Hello {
+            version: PROTOCOL_VERSION - 1,
+            name: "Old".to_string(),
+            spectate: false,
+        };
+        let reply = say_hello(&mut server, &hello);
+        let Some(ServerPacket::Reject { reason }) = reply else {
+            panic!("expected a reject, got {reply:?}");
+        };
+        assert!(reason.contains(&PROTOCOL_VERSION.to_string()), "{reason}");
+        assert!(server.connections.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn overlong_names_are_cut_before_they_reach_snapshots() {
+        let mut server = server(NetConditions::default());
+        let hello = ClientPacket::Hello {
+            version: PROTOCOL_VERSION,
+            name: "é".repeat(30_000),
+            spectate: false,
+        };
+        let reply = say_hello(&mut server, &hello);
+        let Some(ServerPacket::Welcome { player, .. }) = reply else {
+            panic!("expected a welcome, got {reply:?}");
+        };
+        assert_eq!(player, Some(0));
+        server.tick(Instant::now()).unwrap();
+
+        let snapshot = server.snapshot();
+        let player = snapshot.snakes.iter().find(|snake| !snake.bot).unwrap();
+        assert_eq!(player.name, "é".repeat(MAX_NAME_LENGTH));
+        let (_, cut) = encode_to_fit(&mut ServerPacket::Snapshot(snapshot));
+        assert_eq!(cut, 0);
+    }
+
+    #[test]
+    fn plays_over_loopback_with_latency_and_loss() {
+        let conditions = NetConditions {
+            latency: Duration::from_millis(30),
+            jitter: Duration::from_millis(10),
+            loss: 0.2,
+        };
+        let mut server = server(conditions);
+        let address = server.local_addr().unwrap();
+        let mut clients = vec![
+            Client::connect(address, "Player", conditions).unwrap(),
+            Client::spectate(address, "Watcher", conditions).unwrap(),
+        ];
+        // Hellos and welcomes get lost too, so joining can take a few tries.
+        let give_up = Instant::now() + Duration::from_secs(10);
+        while clients[0].state == ClientState::Connecting
+            || clients[1].state == ClientState::Connecting
+        {
+            assert!(Instant::now() < give_up, "never joined");
+            play(
+                &mut server,
+                &mut clients,
+                Duration::from_millis(100),
+                |_| {},
+            );
+        }
+        play(
+            &mut server,
+            &mut clients,
+            Duration::from_millis(1500),
+            |_| {},
+        );
+
+        let (player, watcher) = (&clients[0], &clients[1]);
+        assert_eq!(player.state, ClientState::Connected { player: 0 });
+        assert_eq!(watcher.state, ClientState::Watching);
+        for client in [player, watcher] {
+            assert!(client.stats.snapshots > 10, "{:?}", client.stats);
+            assert!(client.stats.snapshots_missed > 0, "{:?}", client.stats);
+        }
+        // Loss on both ends still leaves the player acknowledged, with a round
+        // trip of about both latencies.
+        assert!(player.link_stats().dropped > 0);
+        assert!(
+            player.stats.round_trip > 0.05,
+            "{}",
+            player.stats.round_trip
+        );
+        let frame = player.frame(Instant::now()).unwrap();
+        assert_eq!(frame.snakes.len(), 1);
+        assert!(frame.snakes[0].local);
+    }
+
+    #[test]
+    fn oversized_snapshots_are_cut_to_fit_a_datagram() {
+        let mut server = server(NetConditions::default());
+        let address = server.local_addr().unwrap();
+        // A spectator draws bodies straight from the snapshots.
+        let mut clients = vec![
+            Client::connect(address, "Player", NetConditions::default()).unwrap(),
+            Client::spectate(address, "Watcher", NetConditions::default()).unwrap(),
+        ];
+        play(
+            &mut server,
+            &mut clients,
+            Duration::from_millis(300),
+            |_| {},
+        );
+        assert_eq!(clients[1].state, ClientState::Watching);
+
+        // Far longer than a datagram can carry, kept clear of the head.
+        let grow = |server: &mut Server| {
+            let snake = &mut server.simulation.snakes[0];
+            let head = snake.segments[0].clone();
+            snake.segments.truncate(1);
+            snake.segments.extend((0..20_000).map(|index| {
+                let mut segment = head.clone();
+                segment.position = Vec2::new(
+                    -1100.0 + (index % 200) as f32 * 11.0,
+                    700.0 - (index / 200) as f32 * 3.0,
+                );
+                segment
+            }));
+        };
+        let before = clients[1].stats.snapshots;
+        play(&mut server, &mut clients, Duration::from_millis(300), grow);
+
+        let snapshot = ServerPacket::Snapshot(server.snapshot());
+        assert!(encode(&snapshot).len() > MAX_PACKET_SIZE);
+        assert!(clients[1].stats.snapshots > before + 5);
+        let frame = clients[1].frame(Instant::now()).unwrap();
+        let length = frame.snakes[0].segments.len();
+        assert!(length > 1000 && length < 20_000, "{length}");
+    }
+}
@@ -0,0 +1,602 @@
+use std::{
+    fmt,
+    net::{SocketAddr, ToSocketAddrs},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    prelude::*,
+    render::camera::ScalingMode,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    window::PrimaryWindow,
+};
+use bevy_snake::{
+    cli::{parse_millis, parse_share},
+    net::{Client, ClientState, NetConditions, NetFrame, Replay},
+    sim::{
+        rules::{ARENA_SIZE, COIN_RADIUS, SNAKE_RADIUS},
+        Steer,
+    },
+};
+
+use crate::spectate::{Followed, Playback, SpectatePlugin, SpectateSet};
+
+/// Plays or watches on a server instead of playing locally, or plays back a
+/// recording, when the game is started with `--connect`, `--watch` or
+/// `--replay`. The server runs the game; this only sends the player's
+/// steering and draws what comes back.
+pub struct OnlinePlugin(pub OnlineOptions);
+
+impl Plugin for OnlinePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.0.clone())
+            .insert_resource(OnlineConfig::default())
+            .init_resource::<OnlineAssets>()
+            .init_resource::<ViewFrame>()
+            .add_plugins(SpectatePlugin)
+            .add_systems(Startup, (open_session, setup_view))
+            .add_systems(
+                Update,
+                (
+                    update_session.run_if(resource_exists::<Session>),
+                    draw_frame,
+                    follow_camera,
+                    update_hud,
+                )
+                    .chain()
+                    .after(SpectateSet),
+            );
+    }
+}
+
+/// Where the snapshots come from.
+#[derive(Debug, Clone)]
+pub enum OnlineSource {
+    Play(SocketAddr),
+    Watch(SocketAddr),
+    Replay(PathBuf),
+}
+
+impl OnlineSource {
+    /// Watching live or a replay, rather than playing.
+    pub fn is_spectating(&self) -> bool {
+        !matches!(self, OnlineSource::Play(_))
+    }
+}
+
+impl fmt::Display for OnlineSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OnlineSource::Play(address) | OnlineSource::Watch(address) => write!(f, "{address}"),
+            OnlineSource::Replay(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct OnlineOptions {
+    pub source: OnlineSource,
+    pub name: String,
+    /// Simulated trouble on everything this client sends, for testing.
+    pub conditions: NetConditions,
+}
+
+impl OnlineOptions {
+    /// Reads `--connect`, `--watch` or `--replay`, along with `--name`,
+    /// `--latency-ms`, `--jitter-ms` and `--loss`, from the command line.
+    /// `None` without any of the first three.
+    pub fn from_args() -> Option<Result<OnlineOptions, String>> {
+        let args: Vec<String> = std::env::args().collect();
+        let value = |flag: &str| {
+            args.iter()
+                .position(|arg| arg == flag)
+                .and_then(|index| args.get(index + 1))
+        };
+        let sources: Vec<(&str, &String)> = ["--connect", "--watch", "--replay"]
+            .into_iter()
+            .filter_map(|flag| value(flag).map(|source| (flag, source)))
+            .collect();
+        match sources.as_slice() {
+            [] => None,
+            [(flag, source)] => Some(Self::parse(flag, source, value)),
+            _ => Some(Err(
+                "--connect, --watch and --replay can't be combined".to_string()
+            )),
+        }
+    }
+
+    fn parse<'a>(
+        flag: &str,
+        source: &str,
+        value: impl Fn(&str) -> Option<&'a String>,
+    ) -> Result<OnlineOptions, String> {
+        // Each of these is optional, and a bad value is an error naming its flag.
+        let millis = |flag: &str| -> Result<Duration, String> {
+            value(flag)
+                .map(|text| parse_millis(text).map_err(|error| format!("{flag}: {error}")))
+                .unwrap_or(Ok(Duration::ZERO))
+        };
+        let loss = value("--loss")
+            .map(|text| parse_share(text).map_err(|error| format!("--loss: {error}")))
+            .unwrap_or(Ok(0.0))?;
+        let address = || {
+            source
+                .to_socket_addrs()
+                .ok()
+                .and_then(|mut addresses| addresses.next())
+                .ok_or_else(|| format!("{flag}: can't resolve `{source}`"))
+        };
+        Ok(OnlineOptions {
+            source: match flag {
+                "--connect" => OnlineSource::Play(address()?),
+                "--watch" => OnlineSource::Watch(address()?),
+                _ => OnlineSource::Replay(PathBuf::from(source)),
+            },
+            name: value("--name")
+                .cloned()
+                .unwrap_or_else(|| "Player".to_string()),
+            conditions: NetConditions {
+                latency: millis("--latency-ms")?,
+                jitter: millis("--jitter-ms")?,
+                loss,
+            },
+        })
+    }
+}
+
+#[derive(Resource)]
+pub struct OnlineConfig {
+    /// World units visible at once, as in the local game.
+    pub view_size: Vec2,
+    /// How quickly the camera catches up with the snake it follows, per second.
+    pub follow_smoothing: f32,
+}
+
+impl Default for OnlineConfig {
+    fn default() -> Self {
+        Self {
+            view_size: Vec2::new(1280.0, 720.0),
+            follow_smoothing: 4.0,
+        }
+    }
+}
+
+/// The connection to the server, as a player or a spectator.
+#[derive(Resource)]
+struct Session(Client);
+
+/// Why there's nothing to show: the socket or the replay couldn't be opened.
+#[derive(Resource)]
+struct SessionError(String);
+
+/// The arena as it should be drawn this frame, from the server or a replay.
+#[derive(Resource, Default)]
+pub struct ViewFrame(pub Option<NetFrame>);
+
+/// Meshes and colors for everything drawn from snapshots, in the default
+/// theme's snake, rival and bot colors. The followed snake of a spectator is
+/// drawn like our own.
+#[derive(Resource)]
+struct OnlineAssets {
+    font: Handle<Font>,
+    segment: Handle<Mesh>,
+    coin: Handle<Mesh>,
+    own: Handle<ColorMaterial>,
+    rival: Handle<ColorMaterial>,
+    bot: Handle<ColorMaterial>,
+    coin_material: Handle<ColorMaterial>,
+}
+
+impl FromWorld for OnlineAssets {
+    fn from_world(world: &mut World) -> Self {
+        let font = world
+            .resource::<AssetServer>()
+            .load("font/FiraSans-Bold.ttf");
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let segment = meshes.add(Circle::new(SNAKE_RADIUS));
+        let coin = meshes.add(Circle::new(COIN_RADIUS));
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        Self {
+            font,
+            segment,
+            coin,
+            own: materials.add(Color::GREEN),
+            rival: materials.add(Color::ORANGE_RED),
+            bot: materials.add(Color::GRAY),
+            coin_material: materials.add(Color::RED),
+        }
+    }
+}
+
+/// Circles reused from frame to frame, since snakes change length constantly.
+#[derive(Component)]
+struct SegmentSprite;
+
+#[derive(Component)]
+struct CoinSprite;
+
+/// Name and score floating over a snake's head.
+#[derive(Component)]
+struct NameTag;
+
+#[derive(Component)]
+struct OnlineHudText;
+
+fn open_session(mut commands: Commands, options: Res<OnlineOptions>) {
+    let opened = match &options.source {
+        OnlineSource::Play(server) => Client::connect(*server, &options.name, options.conditions)
+            .map(|client| commands.insert_resource(Session(client))),
+        OnlineSource::Watch(server) => Client::spectate(*server, &options.name, options.conditions)
+            .map(|client| commands.insert_resource(Session(client))),
+        OnlineSource::Replay(path) => {
+            Replay::load(path).map(|replay| commands.insert_resource(Playback::new(replay)))
+        }
+    };
+    if let Err(error) = opened {
+        error!("Couldn't open {}: {error}", options.source);
+        commands.insert_resource(SessionError(error.to_string()));
+    }
+}
+
+fn setup_view(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    config: Res<OnlineConfig>,
+    assets: Res<OnlineAssets>,
+) {
+    let mut camera = Camera2dBundle::default();
+    camera.projection.scaling_mode = ScalingMode::Fixed {
+        width: config.view_size.x,
+        height: config.view_size.y,
+    };
+    commands.spawn(camera);
+
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: Mesh2dHandle(meshes.add(Rectangle::from_size(ARENA_SIZE))),
+            material: materials.add(Color::rgb(0.1, 0.1, 0.1)),
+            transform: Transform::from_xyz(0.0, 0.0, -10.0),
+            ..default()
+        },
+        Name::new("ArenaFloor"),
+    ));
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: assets.font.clone(),
+                font_size: 24.0,
+                color: Color::rgb(0.9, 0.9, 0.9),
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..default()
+        }),
+        OnlineHudText,
+    ));
+}
+
+/// Steers toward the cursor while it's over the window, or turns with the
+/// arrow keys or A and D, the same controls as the local game. Spectators
+/// only keep the connection alive.
+fn update_session(
+    mut session: ResMut<Session>,
+    mut view_frame: ResMut<ViewFrame>,
+    keys: Res<ButtonInput<KeyCode>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+) {
+    let turn = [KeyCode::ArrowLeft, KeyCode::KeyA]
+        .into_iter()
+        .any(|key| keys.pressed(key)) as i32 as f32
+        - [KeyCode::ArrowRight, KeyCode::KeyD]
+            .into_iter()
+            .any(|key| keys.pressed(key)) as i32 as f32;
+    let cursor = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .zip(camera_query.get_single().ok())
+        .and_then(|(cursor, (camera, transform))| camera.viewport_to_world_2d(transform, cursor));
+    let steer = match cursor {
+        _ if session.0.player().is_none() => Steer::Hold,
+        _ if turn != 0.0 => Steer::Turn(turn),
+        Some(target) => Steer::Toward(target),
+        None => Steer::Hold,
+    };
+    let now = Instant::now();
+    session.0.update(now, steer);
+    view_frame.0 = session.0.frame(now);
+}
+
+fn draw_frame(
+    mut commands: Commands,
+    view_frame: Res<ViewFrame>,
+    followed: Res<Followed>,
+    assets: Res<OnlineAssets>,
+    mut segment_query: Query<
+        (&mut Transform, &mut Handle<ColorMaterial>, &mut Visibility),
+        (With<SegmentSprite>, Without<CoinSprite>, Without<NameTag>),
+    >,
+    mut coin_query: Query<(&mut Transform, &mut Visibility), (With<CoinSprite>, Without<NameTag>)>,
+    mut tag_query: Query<(&mut Transform, &mut Text, &mut Visibility), With<NameTag>>,
+) {
+    let (snakes, coins) = view_frame.0.as_ref().map_or((&[][..], &[][..]), |frame| {
+        (&frame.snakes[..], &frame.coins[..])
+    });
+
+    // Heads are drawn over bodies, and the highlighted snake over everyone else.
+    let mut segments = Vec::new();
+    let mut tags = Vec::new();
+    for snake in snakes.iter().filter(|snake| snake.alive) {
+        let highlighted = snake.local || followed.0 == Some(snake.id);
+        let material = match (highlighted, snake.bot) {
+            (true, _) => &assets.own,
+            (false, true) => &assets.bot,
+            (false, false) => &assets.rival,
+        };
+        let layer = if highlighted { 2.0 } else { 1.0 };
+        for (index, position) in snake.segments.iter().enumerate() {
+            let depth = layer - (index as f32 / snake.segments.len() as f32) * 0.5;
+            segments.push((position.extend(depth), material.clone()));
+        }
+        if !snake.local {
+            let above = snake.segments[0] + Vec2::Y * SNAKE_RADIUS * 3.0;
+            tags.push((
+                above.extend(5.0),
+                format!("{}  {}", snake.name, snake.score),
+            ));
+        }
+    }
+
+    let mut pool = segment_query.iter_mut();
+    for (position, material) in segments {
+        match pool.next() {
+            Some((mut transform, mut handle, mut visibility)) => {
+                transform.translation = position;
+                *handle = material;
+                *visibility = Visibility::Visible;
+            }
+            None => {
+                commands.spawn((
+                    MaterialMesh2dBundle {
+                        mesh: Mesh2dHandle(assets.segment.clone()),
+                        material,
+                        transform: Transform::from_translation(position),
+                        ..default()
+                    },
+                    SegmentSprite,
+                ));
+            }
+        }
+    }
+    for (_, _, mut visibility) in pool {
+        *visibility = Visibility::Hidden;
+    }
+
+    let mut pool = coin_query.iter_mut();
+    for coin in coins.iter() {
+        match pool.next() {
+            Some((mut transform, mut visibility)) => {
+                transform.translation = coin.extend(0.0);
+                *visibility = Visibility::Visible;
+            }
+            None => {
+                commands.spawn((
+                    MaterialMesh2dBundle {
+                        mesh: Mesh2dHandle(assets.coin.clone()),
+                        material: assets.coin_material.clone(),
+                        transform: Transform::from_translation(coin.extend(0.0)),
+                        ..default()
+                    },
+                    CoinSprite,
+                ));
+            }
+        }
+    }
+    for (_, mut visibility) in pool {
+        *visibility = Visibility::Hidden;
+    }
+
+    let mut pool = tag_query.iter_mut();
+    for (position, label) in tags {
+        match pool.next() {
+            Some((mut transform, mut text, mut visibility)) => {
+                transform.translation = position;
+                text.sections[0].value = label;
+                *visibility = Visibility::Visible;
+            }
+            None => {
+                commands.spawn((
+                    Text2dBundle {
+                        text: Text::from_section(
+                            label,
+                            TextStyle {
+                                font: assets.font.clone(),
+                                font_size: 20.0,
+                                color: Color::rgb(0.9, 0.9, 0.9),
+                            },
+                        ),
+                        transform: Transform::from_translation(position),
+                        ..default()
+                    },
+                    NameTag,
+                ));
+            }
+        }
+    }
+    for (_, _, mut visibility) in pool {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+/// Tracks our head, or the snake a spectator follows, and shows the whole
+/// arena otherwise.
+fn follow_camera(
+    view_frame: Res<ViewFrame>,
+    followed: Res<Followed>,
+    config: Res<OnlineConfig>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
+    time: Res<Time>,
+) {
+    let Ok((mut transform, mut projection)) = camera_query.get_single_mut() else {
+        return;
+    };
+    let head = view_frame.0.as_ref().and_then(|frame| {
+        frame
+            .snakes
+            .iter()
+            .filter(|snake| snake.alive)
+            .find(|snake| snake.local || followed.0 == Some(snake.id))
+            .map(|snake| snake.segments[0])
+    });
+    let arena_fit = (ARENA_SIZE / config.view_size).max_element();
+    let (target, scale) = head.map_or((Vec2::ZERO, arena_fit), |head| (head, 1.0));
+
+    let blend = 1.0 - (-config.follow_smoothing * time.delta_seconds()).exp();
+    projection.scale += (scale - projection.scale) * blend;
+    let half_view = config.view_size / 2.0 * projection.scale;
+    let limit = (ARENA_SIZE / 2.0 - half_view).max(Vec2::ZERO);
+    let position = transform
+        .translation
+        .truncate()
+        .lerp(target, blend)
+        .clamp(-limit, limit);
+    transform.translation = position.extend(transform.translation.z);
+}
+
+fn update_hud(
+    session: Option<Res<Session>>,
+    session_error: Option<Res<SessionError>>,
+    playback: Option<Res<Playback>>,
+    view_frame: Res<ViewFrame>,
+    followed: Res<Followed>,
+    options: Res<OnlineOptions>,
+    mut text_query: Query<&mut Text, With<OnlineHudText>>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+    if let Some(error) = session_error {
+        text.sections[0].value = format!("Couldn't open {}: {}", options.source, error.0);
+        return;
+    }
+    let state = session.as_ref().map(|session| &session.0.state);
+    let mut lines = match state {
+        Some(ClientState::Connecting) => vec![format!("Connecting to {}...", options.source)],
+        Some(ClientState::Rejected(reason)) => {
+            vec![format!("{} turned us away: {reason}", options.source)]
+        }
+        _ => match &view_frame.0 {
+            Some(frame) => scoreboard(frame, followed.0),
+            None => vec!["Waiting for players...".to_string()],
+        },
+    };
+
+    if let Some(client) = session.as_ref().map(|session| &session.0) {
+        let stats = client.stats;
+        let expected = stats.snapshots + stats.snapshots_missed;
+        let loss = if expected == 0 {
+            0.0
+        } else {
+            stats.snapshots_missed as f32 / expected as f32 * 100.0
+        };
+        match client.state {
+            ClientState::Connected { .. } => lines.push(format!(
+                "ping {:.0} ms  loss {loss:.0}%  corrections {}",
+                stats.round_trip * 1000.0,
+                stats.corrections
+            )),
+            ClientState::Watching => lines.push(format!("watching  loss {loss:.0}%")),
+            _ => {}
+        }
+    }
+    if let Some(playback) = playback {
+        lines.push(playback.status());
+    }
+    if options.source.is_spectating() {
+        lines.push("Tab / 1-9: follow a snake   0: whole arena".to_string());
+    }
+    text.sections[0].value = lines.join("\n");
+}
+
+/// Every snake, best first, with the one being followed marked.
+fn scoreboard(frame: &NetFrame, followed: Option<u8>) -> Vec<String> {
+    let mut lines = vec![if frame.round_over {
+        format!("Round {} is over", frame.round)
+    } else {
+        format!("Round {}", frame.round)
+    }];
+    let mut snakes: Vec<_> = frame.snakes.iter().collect();
+    snakes.sort_by_key(|snake| std::cmp::Reverse(snake.score));
+    for snake in snakes {
+        let marker = if followed == Some(snake.id) { "> " } else { "" };
+        let name = if snake.local { "You" } else { &snake.name };
+        let status = if snake.alive {
+            format!("  length {}", snake.segments.len())
+        } else {
+            "  (out)".to_string()
+        };
+        lines.push(format!("{marker}{name}  {}{status}", snake.score));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(flags: &[(&str, &str)]) -> Result<OnlineOptions, String> {
+        let flags: Vec<(&str, String)> = flags
+            .iter()
+            .map(|(flag, value)| (*flag, value.to_string()))
+            .collect();
+        OnlineOptions::parse("--replay", "night.replay", |flag: &str| {
+            flags
+                .iter()
+                .find(|(name, _)| *name == flag)
+                .map(|(_, value)| value)
+        })
+    }
+
+    #[test]
+    fn network_flags_default_to_a_clean_connection() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.conditions, NetConditions::default());
+        assert_eq!(options.name, "Player");
+    }
+
+    #[test]
+    fn network_flags_are_read() {
+        let options = parse(&[
+            ("--latency-ms", "80"),
+            ("--jitter-ms", "12.5"),
+            ("--loss", "0.1"),
+        ])
+        .unwrap();
+        assert_eq!(options.conditions.latency, Duration::from_millis(80));
+        assert_eq!(options.conditions.jitter, Duration::from_micros(12_500));
+        assert_eq!(options.conditions.loss, 0.1);
+    }
+
+    #[test]
+    fn bad_network_flags_are_errors_not_panics() {
+        for flags in [
+            [("--latency-ms", "-20")],
+            [("--jitter-ms", "NaN")],
+            [("--latency-ms", "inf")],
+            [("--loss", "1.5")],
+            [("--loss", "-0.5")],
+            [("--loss", "NaN")],
+        ] {
+            let error = parse(&flags).unwrap_err();
+            assert!(error.starts_with(flags[0].0), "{error}");
+        }
+    }
+}
@@ -0,0 +1,428 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    render::{mesh::PrimitiveTopology, render_asset::RenderAssetUsages, view::NoFrustumCulling},
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    utils::{BoxedFuture, HashMap},
+};
+use rand::prelude::*;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    boost::Boost,
+    event::{CoinCollectedEvent, SnakeDiedEvent},
+    settings::Settings,
+    snake::{SnakeSegment, SnakeSegments},
+};
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<EmitterConfig>()
+            .init_asset_loader::<EmitterLoader>()
+            .insert_resource(Particles::default())
+            .insert_resource(BoostTrail::default())
+            .add_systems(Startup, setup_particles)
+            .add_systems(
+                Update,
+                (
+                    emit_coin_sparkles,
+                    emit_death_burst,
+                    emit_boost_trail,
+                    update_particles,
+                )
+                    .chain(),
+            )
+            .add_systems(PostUpdate, update_particle_mesh);
+    }
+}
+
+/// Upper bound on live particles, so a long boost can't grow the mesh forever.
+const MAX_PARTICLES: usize = 2000;
+
+/// Emitter parameters, loaded from `assets/particles/*.emitter.ron`.
+#[derive(Asset, TypePath, Debug)]
+pub struct EmitterConfig {
+    /// Particles spawned at once for each triggering event.
+    pub burst: usize,
+    /// Particles per second while a continuous emitter is running.
+    pub rate: f32,
+    pub speed: (f32, f32),
+    pub lifetime: (f32, f32),
+    /// Size at birth and at death.
+    pub size: (f32, f32),
+    pub start_color: Color,
+    pub end_color: Color,
+    /// Width in degrees of the cone particles are thrown into.
+    pub spread: f32,
+    /// Fraction of velocity lost per second.
+    pub drag: f32,
+}
+
+/// On-disk form of an [`EmitterConfig`], with colors written as hex strings.
+#[derive(Deserialize)]
+struct EmitterFile {
+    burst: usize,
+    rate: f32,
+    speed: (f32, f32),
+    lifetime: (f32, f32),
+    size: (f32, f32),
+    start_color: String,
+    end_color: String,
+    spread: f32,
+    drag: f32,
+}
+
+impl EmitterFile {
+    /// Rejects values that would panic or divide by zero when particles are
+    /// spawned and aged, so a typo shows up as a load error instead.
+    fn validate(&self) -> Result<(), EmitterLoaderError> {
+        let invalid = |message: String| Err(EmitterLoaderError::Invalid(message));
+        let values = [
+            ("rate", self.rate),
+            ("speed", self.speed.0),
+            ("speed", self.speed.1),
+            ("lifetime", self.lifetime.0),
+            ("lifetime", self.lifetime.1),
+            ("size", self.size.0),
+            ("size", self.size.1),
+            ("spread", self.spread),
+            ("drag", self.drag),
+        ];
+        for (name, value) in values {
+            if !value.is_finite() {
+                return invalid(format!("`{name}` must be a finite number, got {value}"));
+            }
+            if value < 0.0 {
+                return invalid(format!("`{name}` must not be negative, got {value}"));
+            }
+        }
+        for (name, (min, max)) in [("speed", self.speed), ("lifetime", self.lifetime)] {
+            if min > max {
+                return invalid(format!("`{name}` minimum {min} is above its maximum {max}"));
+            }
+        }
+        if self.lifetime.0 <= 0.0 {
+            return invalid(format!(
+                "`lifetime` must be above zero, got {}",
+                self.lifetime.0
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum EmitterLoaderError {
+    #[error("could not read emitter file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse emitter file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("invalid color `{0}` in emitter file")]
+    Color(String),
+    #[error("invalid emitter file: {0}")]
+    Invalid(String),
+}
+
+#[derive(Default)]
+struct EmitterLoader;
+
+impl AssetLoader for EmitterLoader {
+    type Asset = EmitterConfig;
+    type Settings = ();
+    type Error = EmitterLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<EmitterConfig, EmitterLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let file: EmitterFile = ron::de::from_bytes(&bytes)?;
+            file.validate()?;
+            let color = |hex: String| Color::hex(&hex).map_err(|_| EmitterLoaderError::Color(hex));
+
+            Ok(EmitterConfig {
+                burst: file.burst,
+                rate: file.rate,
+                speed: file.speed,
+                lifetime: file.lifetime,
+                size: file.size,
+                start_color: color(file.start_color)?,
+                end_color: color(file.end_color)?,
+                spread: file.spread,
+                drag: file.drag,
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["emitter.ron"]
+    }
+}
+
+#[derive(Resource)]
+struct ParticleEmitters {
+    coin_sparkle: Handle<EmitterConfig>,
+    death_burst: Handle<EmitterConfig>,
+    boost_trail: Handle<EmitterConfig>,
+}
+
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    age: f32,
+    lifetime: f32,
+    size: (f32, f32),
+    colors: (Vec4, Vec4),
+    drag: f32,
+}
+
+#[derive(Resource, Default)]
+struct Particles(Vec<Particle>);
+
+impl Particles {
+    /// Throws `count` particles from `origin` into a cone around `direction`.
+    fn emit(&mut self, config: &EmitterConfig, count: usize, origin: Vec2, direction: Vec2) {
+        let mut rng = thread_rng();
+        let base_angle = Vec2::X.angle_between(direction.try_normalize().unwrap_or(Vec2::X));
+        let half_spread = (config.spread.to_radians() / 2.0).min(TAU / 2.0);
+        let count = count.min(MAX_PARTICLES.saturating_sub(self.0.len()));
+
+        for _ in 0..count {
+            let angle = base_angle + rng.gen_range(-half_spread..=half_spread);
+            let speed = rng.gen_range(config.speed.0..=config.speed.1);
+            self.0.push(Particle {
+                position: origin,
+                velocity: Vec2::from_angle(angle) * speed,
+                age: 0.0,
+                lifetime: rng.gen_range(config.lifetime.0..=config.lifetime.1),
+                size: config.size,
+                colors: (
+                    Vec4::from_array(config.start_color.as_linear_rgba_f32()),
+                    Vec4::from_array(config.end_color.as_linear_rgba_f32()),
+                ),
+                drag: config.drag,
+            });
+        }
+    }
+}
+
+/// Fractional particles owed by each snake's boost trail, carried between frames.
+#[derive(Resource, Default)]
+struct BoostTrail(HashMap<Entity, f32>);
+
+#[derive(Component)]
+struct ParticleMesh;
+
+fn setup_particles(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(ParticleEmitters {
+        coin_sparkle: asset_server.load("particles/coin_sparkle.emitter.ron"),
+        death_burst: asset_server.load("particles/death_burst.emitter.ron"),
+        boost_trail: asset_server.load("particles/boost_trail.emitter.ron"),
+    });
+
+    let mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, Vec::<[f32; 3]>::new())
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, Vec::<[f32; 4]>::new());
+
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: Mesh2dHandle(meshes.add(mesh)),
+            material: materials.add(Color::WHITE),
+            transform: Transform::from_xyz(0.0, 0.0, 2.0),
+            ..default()
+        },
+        NoFrustumCulling,
+        ParticleMesh,
+        Name::new("Particles"),
+    ));
+}
+
+fn emit_coin_sparkles(
+    mut coin_collected_event: EventReader<CoinCollectedEvent>,
+    mut particles: ResMut<Particles>,
+    emitters: Res<ParticleEmitters>,
+    configs: Res<Assets<EmitterConfig>>,
+    settings: Res<Settings>,
+) {
+    let Some(config) = configs.get(&emitters.coin_sparkle) else {
+        return;
+    };
+    for event in coin_collected_event.read() {
+        if !settings.reduced_motion {
+            particles.emit(config, config.burst, event.position, Vec2::X);
+        }
+    }
+}
+
+fn emit_death_burst(
+    mut snake_died_event: EventReader<SnakeDiedEvent>,
+    mut particles: ResMut<Particles>,
+    emitters: Res<ParticleEmitters>,
+    configs: Res<Assets<EmitterConfig>>,
+    settings: Res<Settings>,
+) {
+    let Some(config) = configs.get(&emitters.death_burst) else {
+        return;
+    };
+    for event in snake_died_event.read() {
+        if settings.reduced_motion {
+            continue;
+        }
+        for position in event.body.iter() {
+            particles.emit(config, config.burst, *position, Vec2::X);
+        }
+    }
+}
+
+fn emit_boost_trail(
+    mut particles: ResMut<Particles>,
+    mut trail: ResMut<BoostTrail>,
+    emitters: Res<ParticleEmitters>,
+    configs: Res<Assets<EmitterConfig>>,
+    snake_query: Query<(Entity, &Boost, &SnakeSegments)>,
+    segment_query: Query<&Transform, With<SnakeSegment>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    let Some(config) = configs.get(&emitters.boost_trail) else {
+        return;
+    };
+    // Snakes that stopped boosting or are gone start their next trail from zero.
+    trail.0.retain(|snake, _| {
+        snake_query
+            .get(*snake)
+            .is_ok_and(|(_, boost, _)| boost.active)
+    });
+    if settings.reduced_motion {
+        trail.0.clear();
+        return;
+    }
+
+    for (snake, boost, segments) in snake_query.iter() {
+        if !boost.active {
+            continue;
+        }
+        let Some(tail) = segments
+            .0
+            .last()
+            .and_then(|tail| segment_query.get(*tail).ok())
+        else {
+            continue;
+        };
+
+        let owed = trail.0.entry(snake).or_default();
+        *owed += config.rate * time.delta_seconds();
+        let count = *owed as usize;
+        *owed -= count as f32;
+
+        let backward = -(tail.rotation * Vec3::X).truncate();
+        particles.emit(config, count, tail.translation.truncate(), backward);
+    }
+}
+
+fn update_particles(mut particles: ResMut<Particles>, settings: Res<Settings>, time: Res<Time>) {
+    if settings.reduced_motion {
+        particles.0.clear();
+        return;
+    }
+    let delta = time.delta_seconds();
+    particles.0.retain_mut(|particle| {
+        particle.age += delta;
+        particle.velocity *= (1.0 - particle.drag * delta).max(0.0);
+        particle.position += particle.velocity * delta;
+        particle.age < particle.lifetime
+    });
+}
+
+fn update_particle_mesh(
+    particles: Res<Particles>,
+    particle_mesh_query: Query<&Mesh2dHandle, With<ParticleMesh>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let Ok(Mesh2dHandle(handle)) = particle_mesh_query.get_single() else {
+        return;
+    };
+    let Some(mesh) = meshes.get_mut(handle) else {
+        return;
+    };
+
+    let mut positions = Vec::with_capacity(particles.0.len() * 6);
+    let mut colors = Vec::with_capacity(particles.0.len() * 6);
+    for particle in particles.0.iter() {
+        let t = (particle.age / particle.lifetime).clamp(0.0, 1.0);
+        let half_size = (particle.size.0 + (particle.size.1 - particle.size.0) * t) / 2.0;
+        let color = particle.colors.0.lerp(particle.colors.1, t).to_array();
+
+        let Vec2 { x, y } = particle.position;
+        let (left, right) = (x - half_size, x + half_size);
+        let (bottom, top) = (y - half_size, y + half_size);
+        positions.extend_from_slice(&[
+            [left, bottom, 0.0],
+            [right, bottom, 0.0],
+            [right, top, 0.0],
+            [left, bottom, 0.0],
+            [right, top, 0.0],
+            [left, top, 0.0],
+        ]);
+        colors.extend_from_slice(&[color; 6]);
+    }
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> EmitterFile {
+        ron::de::from_str(source).unwrap()
+    }
+
+    #[test]
+    fn shipped_emitters_are_valid() {
+        for name in ["boost_trail", "coin_sparkle", "death_burst"] {
+            let path = format!("assets/particles/{name}.emitter.ron");
+            let source = std::fs::read_to_string(&path).unwrap();
+            assert!(parse(&source).validate().is_ok(), "{path}");
+        }
+    }
+
+    #[test]
+    fn rejects_ranges_that_would_panic() {
+        let base = std::fs::read_to_string("assets/particles/coin_sparkle.emitter.ron").unwrap();
+
+        let mut file = parse(&base);
+        file.speed = (200.0, 100.0);
+        assert!(file.validate().is_err());
+
+        let mut file = parse(&base);
+        file.lifetime = (0.0, 0.5);
+        assert!(file.validate().is_err());
+
+        let mut file = parse(&base);
+        file.size = (f32::NAN, 2.0);
+        assert!(file.validate().is_err());
+
+        let mut file = parse(&base);
+        file.drag = f32::INFINITY;
+        assert!(file.validate().is_err());
+    }
+}
@@ -0,0 +1,402 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+use rand::prelude::*;
+
+use crate::{
+    accessibility::FoodMeshes,
+    arena::Arena,
+    coin::Coin,
+    event::{EffectEndedEvent, EffectStartedEvent, GameOverEvent},
+    game_state::{GameMode, GameState},
+    settings::Settings,
+    snake::{GameRules, Player, SnakeHead},
+    theme::ThemeMaterials,
+};
+
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        let config = PowerUpConfig::default();
+        app.insert_resource(PowerUpTimer(Timer::from_seconds(
+            config.spawn_interval,
+            TimerMode::Repeating,
+        )))
+        .insert_resource(config)
+        .add_systems(Startup, setup_effects_label)
+        .add_systems(
+            Update,
+            (
+                spawn_power_up,
+                collect_power_up,
+                tick_active_effects,
+                apply_coin_magnet,
+                clear_power_ups,
+                log_effect_events,
+                effects_text_update_system,
+            ),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PowerUpKind {
+    Magnet,
+    Ghost,
+    SlowMotion,
+    ScoreDoubler,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 4] = [
+        PowerUpKind::Magnet,
+        PowerUpKind::Ghost,
+        PowerUpKind::SlowMotion,
+        PowerUpKind::ScoreDoubler,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PowerUpKind::Magnet => "Magnet",
+            PowerUpKind::Ghost => "Ghost",
+            PowerUpKind::SlowMotion => "Slow",
+            PowerUpKind::ScoreDoubler => "x2 Score",
+        }
+    }
+
+    /// Position in [`PowerUpKind::ALL`], for per-kind tables.
+    pub fn index(&self) -> usize {
+        match self {
+            PowerUpKind::Magnet => 0,
+            PowerUpKind::Ghost => 1,
+            PowerUpKind::SlowMotion => 2,
+            PowerUpKind::ScoreDoubler => 3,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct PowerUpConfig {
+    pub spawn_interval: f32,
+    pub spawn_chance: f64,
+    /// Seconds a pickup stays on the field before it disappears.
+    pub pickup_lifetime: f32,
+    pub pickup_size: f32,
+    /// Effects that add their duration to the time left when picked up again.
+    /// Every other effect restarts at its full duration instead.
+    pub stacking_effects: Vec<PowerUpKind>,
+    pub magnet_duration: f32,
+    pub magnet_radius: f32,
+    pub magnet_pull_speed: f32,
+    pub ghost_duration: f32,
+    pub slow_motion_duration: f32,
+    pub slow_motion_scale: f32,
+    pub score_doubler_duration: f32,
+}
+
+impl Default for PowerUpConfig {
+    fn default() -> Self {
+        Self {
+            spawn_interval: 6.0,
+            spawn_chance: 0.6,
+            pickup_lifetime: 10.0,
+            pickup_size: 14.0,
+            stacking_effects: vec![PowerUpKind::ScoreDoubler],
+            magnet_duration: 8.0,
+            magnet_radius: 200.0,
+            magnet_pull_speed: 300.0,
+            ghost_duration: 5.0,
+            slow_motion_duration: 6.0,
+            slow_motion_scale: 0.5,
+            score_doubler_duration: 10.0,
+        }
+    }
+}
+
+impl PowerUpConfig {
+    fn duration(&self, kind: PowerUpKind) -> f32 {
+        match kind {
+            PowerUpKind::Magnet => self.magnet_duration,
+            PowerUpKind::Ghost => self.ghost_duration,
+            PowerUpKind::SlowMotion => self.slow_motion_duration,
+            PowerUpKind::ScoreDoubler => self.score_doubler_duration,
+        }
+    }
+}
+
+/// Effects running on a snake, each with the time it has left.
+#[derive(Component, Default)]
+pub struct ActiveEffects(HashMap<PowerUpKind, Timer>);
+
+impl ActiveEffects {
+    pub fn is_active(&self, kind: PowerUpKind) -> bool {
+        self.0.contains_key(&kind)
+    }
+
+    pub fn remaining(&self, kind: PowerUpKind) -> Option<f32> {
+        self.0.get(&kind).map(|timer| timer.remaining_secs())
+    }
+
+    /// Factor applied to `SnakeSpeed` while the effects are running.
+    pub fn speed_scale(&self, config: &PowerUpConfig) -> f32 {
+        if self.is_active(PowerUpKind::SlowMotion) {
+            config.slow_motion_scale
+        } else {
+            1.0
+        }
+    }
+
+    pub fn score_multiplier(&self) -> u32 {
+        if self.is_active(PowerUpKind::ScoreDoubler) {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Starts or extends `kind`. Returns `true` if the effect was not already running.
+    fn activate(&mut self, kind: PowerUpKind, duration: f32, stack: bool) -> bool {
+        match self.0.get_mut(&kind) {
+            Some(timer) => {
+                let mut duration = Duration::from_secs_f32(duration);
+                if stack {
+                    duration += timer.remaining();
+                }
+                timer.set_duration(duration);
+                timer.reset();
+                false
+            }
+            None => {
+                self.0
+                    .insert(kind, Timer::from_seconds(duration, TimerMode::Once));
+                true
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+    lifetime: Timer,
+}
+
+#[derive(Resource)]
+struct PowerUpTimer(Timer);
+
+#[derive(Component)]
+struct EffectsText;
+
+fn spawn_power_up(
+    mut commands: Commands,
+    arena: Res<Arena>,
+    food_meshes: Res<FoodMeshes>,
+    settings: Res<Settings>,
+    theme_materials: Res<ThemeMaterials>,
+    mut power_up_timer: ResMut<PowerUpTimer>,
+    config: Res<PowerUpConfig>,
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+) {
+    if state.get() != &GameState::InGame {
+        return;
+    }
+    if !power_up_timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let mut rng = thread_rng();
+    if !rng.gen_bool(config.spawn_chance) {
+        return;
+    }
+
+    let Vec2 { x, y } = arena.random_point(30.0);
+    let kind = *PowerUpKind::ALL.choose(&mut rng).unwrap();
+
+    commands
+        .spawn(MaterialMesh2dBundle {
+            mesh: Mesh2dHandle(food_meshes.power_up(kind, settings.shape_coded_food)),
+            material: theme_materials.power_up(kind).clone(),
+            transform: Transform::from_xyz(x, y, 0.0),
+            ..default()
+        })
+        .insert(PowerUp {
+            kind,
+            lifetime: Timer::from_seconds(config.pickup_lifetime, TimerMode::Once),
+        })
+        .insert(Name::new("PowerUp"));
+}
+
+fn collect_power_up(
+    mut commands: Commands,
+    mut power_up_query: Query<(Entity, &Transform, &mut PowerUp)>,
+    mut head_query: Query<(&Transform, &mut ActiveEffects), With<SnakeHead>>,
+    mut effect_started_event: EventWriter<EffectStartedEvent>,
+    config: Res<PowerUpConfig>,
+    rules: Res<GameRules>,
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+) {
+    if state.get() != &GameState::InGame {
+        return;
+    }
+    for (entity, transform, mut power_up) in power_up_query.iter_mut() {
+        let position = transform.translation.truncate();
+        let collector = head_query.iter_mut().find(|(head_transform, _)| {
+            head_transform.translation.truncate().distance(position)
+                < rules.snake_radius + config.pickup_size / 2.0
+        });
+        if let Some((_, mut effects)) = collector {
+            let kind = power_up.kind;
+            let stack = config.stacking_effects.contains(&kind);
+            if effects.activate(kind, config.duration(kind), stack) {
+                effect_started_event.send(EffectStartedEvent(kind));
+            }
+            commands.entity(entity).despawn();
+        } else if power_up.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn tick_active_effects(
+    mut effects_query: Query<&mut ActiveEffects>,
+    mut effect_ended_event: EventWriter<EffectEndedEvent>,
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+) {
+    if state.get() != &GameState::InGame {
+        return;
+    }
+    for mut effects in effects_query.iter_mut() {
+        effects.0.retain(|kind, timer| {
+            if timer.tick(time.delta()).finished() {
+                effect_ended_event.send(EffectEndedEvent(*kind));
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+/// Pulls nearby coins toward every head with a magnet running.
+fn apply_coin_magnet(
+    config: Res<PowerUpConfig>,
+    head_query: Query<(&Transform, &ActiveEffects), With<SnakeHead>>,
+    mut coin_query: Query<&mut Transform, (With<Coin>, Without<SnakeHead>)>,
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+) {
+    if state.get() != &GameState::InGame {
+        return;
+    }
+    for (head_transform, effects) in head_query.iter() {
+        if !effects.is_active(PowerUpKind::Magnet) {
+            continue;
+        }
+        let head_position = head_transform.translation.truncate();
+
+        for mut coin_transform in coin_query.iter_mut() {
+            let coin_position = coin_transform.translation.truncate();
+            let to_head = head_position - coin_position;
+            if to_head.length() < config.magnet_radius {
+                let step =
+                    to_head.normalize_or_zero() * config.magnet_pull_speed * time.delta_seconds();
+                coin_transform.translation += step.extend(0.0);
+            }
+        }
+    }
+}
+
+fn clear_power_ups(
+    mut commands: Commands,
+    mut game_over_event: EventReader<GameOverEvent>,
+    mut effects_query: Query<&mut ActiveEffects>,
+    mut effect_ended_event: EventWriter<EffectEndedEvent>,
+    power_up_query: Query<Entity, With<PowerUp>>,
+) {
+    for _ in game_over_event.read() {
+        for entity in power_up_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        for mut effects in effects_query.iter_mut() {
+            for (kind, _) in effects.0.drain() {
+                effect_ended_event.send(EffectEndedEvent(kind));
+            }
+        }
+    }
+}
+
+fn log_effect_events(
+    mut effect_started_event: EventReader<EffectStartedEvent>,
+    mut effect_ended_event: EventReader<EffectEndedEvent>,
+) {
+    for EffectStartedEvent(kind) in effect_started_event.read() {
+        info!("{} effect started", kind.label());
+    }
+    for EffectEndedEvent(kind) in effect_ended_event.read() {
+        info!("{} effect ended", kind.label());
+    }
+}
+
+fn effects_text_update_system(
+    mut query: Query<&mut Text, With<EffectsText>>,
+    effects_query: Query<(&Player, &ActiveEffects)>,
+    settings: Res<Settings>,
+) {
+    let mut snakes: Vec<(&Player, &ActiveEffects)> = effects_query.iter().collect();
+    snakes.sort_by_key(|(player, _)| **player);
+    let value = snakes
+        .iter()
+        .filter_map(|(player, effects)| {
+            let running = PowerUpKind::ALL
+                .iter()
+                .filter_map(|kind| {
+                    effects
+                        .remaining(*kind)
+                        .map(|remaining| format!("{} {remaining:.1}s", kind.label()))
+                })
+                .collect::<Vec<_>>()
+                .join("  ");
+            match settings.game_mode {
+                GameMode::Solo => Some(running),
+                GameMode::Versus if running.is_empty() => None,
+                GameMode::Versus => Some(format!("{}: {running}", player.label())),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("   ");
+    for mut text in &mut query {
+        text.sections[1].value = value.clone();
+    }
+}
+
+fn setup_effects_label(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new(
+                "Effects: ",
+                TextStyle {
+                    font: asset_server.load("font/FiraSans-Bold.ttf"),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            }),
+        ])
+        .with_style(Style {
+            top: Val::Px(24.0),
+            ..Default::default()
+        }),
+        EffectsText,
+    ));
+}
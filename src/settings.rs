@@ -0,0 +1,499 @@
+use bevy::prelude::*;
+
+use crate::{
+    accessibility::ColorPalette,
+    bot::BrainKind,
+    camera::CameraMode,
+    game_state::{GameMode, GameState},
+    theme::{OwnTextColor, Theme, ThemeLibrary},
+};
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::default())
+            .add_systems(Startup, setup_settings_panel)
+            .add_systems(OnEnter(GameState::Paused), show_settings_panel)
+            .add_systems(OnExit(GameState::Paused), hide_settings_panel)
+            .add_systems(
+                Update,
+                (click_setting_buttons, setting_value_text_update_system),
+            );
+    }
+}
+
+const SETTING_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+
+/// Player preferences, edited from the settings panel shown while paused.
+#[derive(Resource)]
+pub struct Settings {
+    pub game_mode: GameMode,
+    /// Computer opponents added to each round, from the next round on.
+    pub bot_count: u8,
+    pub bot_brain: BrainKind,
+    /// Hands the first player's snake to a bot, for demos.
+    pub autopilot: bool,
+    /// Name of the selected [`Theme`].
+    pub theme: String,
+    pub color_palette: ColorPalette,
+    /// Draw each kind of food with its own shape, not just its own color.
+    pub shape_coded_food: bool,
+    pub high_contrast: bool,
+    /// Multiplier for every UI font size.
+    pub text_scale: f32,
+    /// Turns off screen shake, particles and floating popups.
+    pub reduced_motion: bool,
+    /// Relative speed of the whole game clock.
+    pub game_speed: f32,
+    pub camera_mode: CameraMode,
+    /// Multiplier for screen shake; reduced motion turns it off entirely.
+    pub shake_intensity: f32,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    /// Silences everything without losing the volume levels.
+    pub muted: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            game_mode: GameMode::Solo,
+            bot_count: 0,
+            bot_brain: BrainKind::Pathfinding,
+            autopilot: false,
+            theme: "Classic".into(),
+            color_palette: ColorPalette::Theme,
+            shape_coded_food: false,
+            high_contrast: false,
+            text_scale: 1.0,
+            reduced_motion: false,
+            game_speed: 1.0,
+            camera_mode: CameraMode::Follow,
+            shake_intensity: 1.0,
+            master_volume: 1.0,
+            music_volume: 0.8,
+            sfx_volume: 1.0,
+            muted: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Final volume for music, after the master volume and mute.
+    pub fn music_volume(&self) -> f32 {
+        self.bus_volume(self.music_volume)
+    }
+
+    /// Final volume for sound effects, after the master volume and mute.
+    pub fn sfx_volume(&self) -> f32 {
+        self.bus_volume(self.sfx_volume)
+    }
+
+    fn bus_volume(&self, volume: f32) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master_volume * volume
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SettingKind {
+    GameMode,
+    Bots,
+    BotBrain,
+    Autopilot,
+    Theme,
+    ColorPalette,
+    ShapeCodedFood,
+    HighContrast,
+    TextScale,
+    ReducedMotion,
+    GameSpeed,
+    Camera,
+    ScreenShake,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Mute,
+}
+
+/// A titled group of rows in the settings panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SettingSection {
+    Game,
+    Audio,
+    Display,
+}
+
+impl SettingSection {
+    /// Sections in each column of the panel. Seventeen rows in one column
+    /// run off the bottom of a 720 px window.
+    const COLUMNS: [&'static [SettingSection]; 2] = [
+        &[SettingSection::Game, SettingSection::Audio],
+        &[SettingSection::Display],
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SettingSection::Game => "Game",
+            SettingSection::Audio => "Audio",
+            SettingSection::Display => "Display",
+        }
+    }
+
+    fn kinds(&self) -> &'static [SettingKind] {
+        match self {
+            SettingSection::Game => &[
+                SettingKind::GameMode,
+                SettingKind::Bots,
+                SettingKind::BotBrain,
+                SettingKind::Autopilot,
+                SettingKind::GameSpeed,
+                SettingKind::Camera,
+            ],
+            SettingSection::Audio => &[
+                SettingKind::MasterVolume,
+                SettingKind::MusicVolume,
+                SettingKind::SfxVolume,
+                SettingKind::Mute,
+            ],
+            SettingSection::Display => &[
+                SettingKind::Theme,
+                SettingKind::ColorPalette,
+                SettingKind::ShapeCodedFood,
+                SettingKind::HighContrast,
+                SettingKind::TextScale,
+                SettingKind::ReducedMotion,
+                SettingKind::ScreenShake,
+            ],
+        }
+    }
+}
+
+impl SettingKind {
+    fn label(&self) -> &'static str {
+        match self {
+            SettingKind::GameMode => "Mode",
+            SettingKind::Bots => "Bots",
+            SettingKind::BotBrain => "Bot Strategy",
+            SettingKind::Autopilot => "Autopilot",
+            SettingKind::Theme => "Theme",
+            SettingKind::ColorPalette => "Colors",
+            SettingKind::ShapeCodedFood => "Food Shapes",
+            SettingKind::HighContrast => "High Contrast",
+            SettingKind::TextScale => "Text Size",
+            SettingKind::ReducedMotion => "Reduced Motion",
+            SettingKind::GameSpeed => "Game Speed",
+            SettingKind::Camera => "Camera",
+            SettingKind::ScreenShake => "Screen Shake",
+            SettingKind::MasterVolume => "Master Volume",
+            SettingKind::MusicVolume => "Music Volume",
+            SettingKind::SfxVolume => "SFX Volume",
+            SettingKind::Mute => "Mute",
+        }
+    }
+}
+
+/// Moves `value` by `step` increments, staying within `min..=max`.
+fn step_value(value: f32, step: i32, increment: f32, min: f32, max: f32) -> f32 {
+    let stepped = value + step as f32 * increment;
+    // Round away float drift so values like 1.1 display cleanly.
+    ((stepped / increment).round() * increment).clamp(min, max)
+}
+
+fn on_off(value: bool) -> String {
+    if value { "On" } else { "Off" }.into()
+}
+
+#[derive(Component)]
+struct SettingsPanel;
+
+#[derive(Component)]
+struct SettingValueText(SettingKind);
+
+/// Steps a setting backward (`-1`) or forward (`1`) when pressed.
+#[derive(Component)]
+struct SettingButton {
+    kind: SettingKind,
+    step: i32,
+}
+
+fn show_settings_panel(mut panel_query: Query<&mut Visibility, With<SettingsPanel>>) {
+    for mut visibility in panel_query.iter_mut() {
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn hide_settings_panel(mut panel_query: Query<&mut Visibility, With<SettingsPanel>>) {
+    for mut visibility in panel_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn click_setting_buttons(
+    interaction_query: Query<(&Interaction, &SettingButton), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
+    theme_library: Res<ThemeLibrary>,
+    themes: Res<Assets<Theme>>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if interaction != &Interaction::Pressed {
+            continue;
+        }
+        match button.kind {
+            SettingKind::GameMode => {
+                settings.game_mode = settings.game_mode.cycle(button.step);
+            }
+            SettingKind::Bots => {
+                settings.bot_count = (settings.bot_count as i32 + button.step).clamp(0, 3) as u8;
+            }
+            SettingKind::BotBrain => {
+                settings.bot_brain = settings.bot_brain.cycle(button.step);
+            }
+            SettingKind::Autopilot => {
+                settings.autopilot = !settings.autopilot;
+            }
+            SettingKind::Theme => {
+                let names = theme_library.names(&themes);
+                if names.is_empty() {
+                    continue;
+                }
+                let current = names
+                    .iter()
+                    .position(|name| name == &settings.theme)
+                    .unwrap_or(0) as i32;
+                let next = (current + button.step).rem_euclid(names.len() as i32);
+                settings.theme = names[next as usize].clone();
+            }
+            SettingKind::ColorPalette => {
+                settings.color_palette = settings.color_palette.cycle(button.step);
+            }
+            SettingKind::ShapeCodedFood => {
+                settings.shape_coded_food = !settings.shape_coded_food;
+            }
+            SettingKind::HighContrast => {
+                settings.high_contrast = !settings.high_contrast;
+            }
+            SettingKind::TextScale => {
+                settings.text_scale = step_value(settings.text_scale, button.step, 0.25, 0.75, 2.0);
+            }
+            SettingKind::ReducedMotion => {
+                settings.reduced_motion = !settings.reduced_motion;
+            }
+            SettingKind::GameSpeed => {
+                settings.game_speed = step_value(settings.game_speed, button.step, 0.1, 0.5, 1.5);
+            }
+            SettingKind::Camera => {
+                settings.camera_mode = settings.camera_mode.cycle(button.step);
+            }
+            SettingKind::ScreenShake => {
+                settings.shake_intensity =
+                    step_value(settings.shake_intensity, button.step, 0.25, 0.0, 1.5);
+            }
+            SettingKind::MasterVolume => {
+                settings.master_volume =
+                    step_value(settings.master_volume, button.step, 0.1, 0.0, 1.0);
+            }
+            SettingKind::MusicVolume => {
+                settings.music_volume =
+                    step_value(settings.music_volume, button.step, 0.1, 0.0, 1.0);
+            }
+            SettingKind::SfxVolume => {
+                settings.sfx_volume = step_value(settings.sfx_volume, button.step, 0.1, 0.0, 1.0);
+            }
+            SettingKind::Mute => {
+                settings.muted = !settings.muted;
+            }
+        }
+    }
+}
+
+fn setting_value_text_update_system(
+    mut query: Query<(&mut Text, &SettingValueText)>,
+    settings: Res<Settings>,
+) {
+    for (mut text, SettingValueText(kind)) in query.iter_mut() {
+        text.sections[0].value = match kind {
+            SettingKind::GameMode => settings.game_mode.label().into(),
+            SettingKind::Bots => settings.bot_count.to_string(),
+            SettingKind::BotBrain => settings.bot_brain.label().into(),
+            SettingKind::Autopilot => on_off(settings.autopilot),
+            SettingKind::Theme => settings.theme.clone(),
+            SettingKind::ColorPalette => settings.color_palette.label().into(),
+            SettingKind::ShapeCodedFood => on_off(settings.shape_coded_food),
+            SettingKind::HighContrast => on_off(settings.high_contrast),
+            SettingKind::TextScale => format!("{:.0}%", settings.text_scale * 100.0),
+            SettingKind::ReducedMotion => on_off(settings.reduced_motion),
+            SettingKind::GameSpeed => format!("{:.1}x", settings.game_speed),
+            SettingKind::Camera => settings.camera_mode.label().into(),
+            SettingKind::ScreenShake => format!("{:.0}%", settings.shake_intensity * 100.0),
+            SettingKind::MasterVolume => format!("{:.0}%", settings.master_volume * 100.0),
+            SettingKind::MusicVolume => format!("{:.0}%", settings.music_volume * 100.0),
+            SettingKind::SfxVolume => format!("{:.0}%", settings.sfx_volume * 100.0),
+            SettingKind::Mute => on_off(settings.muted),
+        };
+    }
+}
+
+fn setup_settings_panel(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("font/FiraSans-Bold.ttf");
+    let text_style = TextStyle {
+        font: font.clone(),
+        font_size: 24.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            SettingsPanel,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(20.0)),
+                        row_gap: Val::Px(6.0),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Settings",
+                        TextStyle {
+                            font_size: 36.0,
+                            ..text_style.clone()
+                        },
+                    ));
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                align_items: AlignItems::FlexStart,
+                                column_gap: Val::Px(40.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for sections in SettingSection::COLUMNS {
+                                spawn_setting_column(parent, sections, &text_style);
+                            }
+                        });
+                });
+        });
+}
+
+fn spawn_setting_column(
+    parent: &mut ChildBuilder,
+    sections: &[SettingSection],
+    text_style: &TextStyle,
+) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|column| {
+            for (index, section) in sections.iter().enumerate() {
+                column.spawn((
+                    TextBundle::from_section(
+                        section.label(),
+                        TextStyle {
+                            font_size: 28.0,
+                            color: Color::GRAY,
+                            ..text_style.clone()
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::top(Val::Px(if index == 0 { 0.0 } else { 12.0 })),
+                        ..default()
+                    }),
+                    OwnTextColor,
+                ));
+                for kind in section.kinds() {
+                    spawn_setting_row(column, *kind, text_style);
+                }
+            }
+        });
+}
+
+fn spawn_setting_row(parent: &mut ChildBuilder, kind: SettingKind, text_style: &TextStyle) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(10.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn(
+                TextBundle::from_section(kind.label(), text_style.clone()).with_style(Style {
+                    width: Val::Px(180.0),
+                    ..default()
+                }),
+            );
+            spawn_setting_button(row, kind, -1, "<", text_style);
+            row.spawn((
+                TextBundle::from_section("", text_style.clone()).with_style(Style {
+                    width: Val::Px(160.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                }),
+                SettingValueText(kind),
+            ));
+            spawn_setting_button(row, kind, 1, ">", text_style);
+        });
+}
+
+fn spawn_setting_button(
+    parent: &mut ChildBuilder,
+    kind: SettingKind,
+    step: i32,
+    label: &str,
+    text_style: &TextStyle,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(30.0),
+                    height: Val::Px(30.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                border_color: BorderColor(Color::WHITE),
+                background_color: SETTING_BUTTON.into(),
+                ..default()
+            },
+            SettingButton { kind, step },
+        ))
+        .with_children(|button| {
+            button.spawn(TextBundle::from_section(label, text_style.clone()));
+        });
+}
@@ -0,0 +1,31 @@
+//! Headless core of the game rules, shared by the game, the bots, the batch
+//! simulator, the training environment and the bot tournament.
+
+mod batch;
+mod brain;
+mod env;
+mod path;
+pub mod protocol;
+mod remote;
+pub mod rules;
+mod tournament;
+mod world;
+
+pub use batch::{
+    run_batch, run_game, BatchConfig, DeathCounts, GameResult, Outcome, Percentiles, Summary,
+};
+pub use brain::{BrainKind, CautiousBrain, GreedyBrain, PathfindingBrain, SnakeBrain, WorldView};
+pub use env::{
+    Action, Env, EnvConfig, Observation, ObservationKind, RewardConfig, StepInfo, Transition,
+    VecEnv,
+};
+pub use path::BodyPath;
+pub use remote::{BotSpec, RemoteBrain, RemoteStats};
+pub use rules::{Difficulty, Level, Rules, Turning};
+pub use tournament::{
+    run_tournament, Entrant, MatchResult, Standing, TournamentConfig, TournamentResult,
+};
+pub use world::{
+    advance_head, head_collision, steer_heading, DeathCause, Segment, SimSnake, Simulation, Steer,
+    StepEvents,
+};
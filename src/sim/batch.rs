@@ -0,0 +1,226 @@
+use std::{ops::Range, thread};
+
+use serde::Serialize;
+
+use super::{BrainKind, DeathCause, Difficulty, Level, Rules, Simulation, Steer};
+
+/// A batch of headless games, all played by the same kind of bot.
+#[derive(Debug, Clone)]
+pub struct BatchConfig {
+    /// One game is played per seed.
+    pub seeds: Range<u64>,
+    pub brain: BrainKind,
+    /// Extra bots of the same kind sharing the arena.
+    pub opponents: usize,
+    pub difficulty: Difficulty,
+    pub level: Level,
+    /// Simulated seconds per tick.
+    pub tick: f32,
+    /// Games still going after this many simulated seconds end as a timeout.
+    pub max_time: f32,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            seeds: 0..100,
+            brain: BrainKind::Pathfinding,
+            opponents: 0,
+            difficulty: Difficulty::Normal,
+            level: Level::Normal,
+            tick: 1.0 / 60.0,
+            max_time: 300.0,
+        }
+    }
+}
+
+/// How the measured snake's game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    OwnBody,
+    OtherSnake,
+    Timeout,
+}
+
+impl Outcome {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Outcome::OwnBody => "own_body",
+            Outcome::OtherSnake => "other_snake",
+            Outcome::Timeout => "timeout",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GameResult {
+    pub seed: u64,
+    pub score: u32,
+    /// Simulated seconds the measured snake stayed alive.
+    pub survival_time: f32,
+    pub outcome: Outcome,
+}
+
+/// Plays one game to the end and reports on the first snake; any others are opponents.
+pub fn run_game(config: &BatchConfig, seed: u64) -> GameResult {
+    let rules = Rules::for_difficulty(config.difficulty).with_level(config.level);
+    let mut simulation =
+        Simulation::new(rules, seed, &Simulation::ring_spawns(config.opponents + 1));
+    let mut brains: Vec<_> = (0..simulation.snakes.len())
+        .map(|_| config.brain.brain())
+        .collect();
+
+    while simulation.snakes[0].is_alive() && simulation.time < config.max_time {
+        let steering: Vec<Steer> = brains
+            .iter_mut()
+            .enumerate()
+            .map(|(index, brain)| {
+                if !simulation.snakes[index].is_alive() {
+                    return Steer::Hold;
+                }
+                brain
+                    .steer(&simulation.view(index))
+                    .map_or(Steer::Hold, Steer::Toward)
+            })
+            .collect();
+        simulation.step(config.tick, &steering);
+    }
+
+    let snake = &simulation.snakes[0];
+    GameResult {
+        seed,
+        score: snake.score,
+        survival_time: snake.age,
+        outcome: match snake.death {
+            Some(DeathCause::OwnBody) => Outcome::OwnBody,
+            Some(DeathCause::Snake(_)) => Outcome::OtherSnake,
+            None => Outcome::Timeout,
+        },
+    }
+}
+
+/// Plays every game in the batch, split across the available cores. Results
+/// come back in seed order.
+pub fn run_batch(config: &BatchConfig) -> Vec<GameResult> {
+    let seeds: Vec<u64> = config.seeds.clone().collect();
+    let threads = thread::available_parallelism().map_or(1, |count| count.get());
+    let chunk_size = seeds.len().div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        let workers: Vec<_> = seeds
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|seed| run_game(config, *seed))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("simulation thread panicked"))
+            .collect()
+    })
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Percentiles {
+    pub mean: f32,
+    pub p10: f32,
+    pub p50: f32,
+    pub p90: f32,
+    pub p99: f32,
+}
+
+impl Percentiles {
+    pub fn of(values: impl Iterator<Item = f32>) -> Percentiles {
+        let mut values: Vec<f32> = values.collect();
+        if values.is_empty() {
+            return Percentiles::default();
+        }
+        values.sort_by(f32::total_cmp);
+        // Nearest-rank percentile.
+        let rank = |percent: f32| {
+            let index = (percent / 100.0 * values.len() as f32).ceil() as usize;
+            values[index.clamp(1, values.len()) - 1]
+        };
+        Percentiles {
+            mean: values.iter().sum::<f32>() / values.len() as f32,
+            p10: rank(10.0),
+            p50: rank(50.0),
+            p90: rank(90.0),
+            p99: rank(99.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeathCounts {
+    pub own_body: usize,
+    pub other_snake: usize,
+    pub timeout: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub games: usize,
+    pub bot: &'static str,
+    pub difficulty: &'static str,
+    pub level: &'static str,
+    pub opponents: usize,
+    pub score: Percentiles,
+    pub survival_time: Percentiles,
+    pub deaths: DeathCounts,
+}
+
+impl Summary {
+    pub fn new(config: &BatchConfig, results: &[GameResult]) -> Summary {
+        let mut deaths = DeathCounts::default();
+        for result in results {
+            match result.outcome {
+                Outcome::OwnBody => deaths.own_body += 1,
+                Outcome::OtherSnake => deaths.other_snake += 1,
+                Outcome::Timeout => deaths.timeout += 1,
+            }
+        }
+        Summary {
+            games: results.len(),
+            bot: config.brain.label(),
+            difficulty: config.difficulty.label(),
+            level: config.level.label(),
+            opponents: config.opponents,
+            score: Percentiles::of(results.iter().map(|result| result.score as f32)),
+            survival_time: Percentiles::of(results.iter().map(|result| result.survival_time)),
+            deaths,
+        }
+    }
+
+    pub const CSV_HEADER: &'static str = "games,bot,difficulty,level,opponents,\
+        score_mean,score_p10,score_p50,score_p90,score_p99,\
+        survival_mean,survival_p10,survival_p50,survival_p90,survival_p99,\
+        deaths_own_body,deaths_other_snake,timeouts";
+
+    pub fn csv_row(&self) -> String {
+        let percentiles = |p: &Percentiles| {
+            format!(
+                "{:.2},{:.2},{:.2},{:.2},{:.2}",
+                p.mean, p.p10, p.p50, p.p90, p.p99
+            )
+        };
+        format!(
+            "{},{},{},{},{},{},{},{},{},{}",
+            self.games,
+            self.bot,
+            self.difficulty,
+            self.level,
+            self.opponents,
+            percentiles(&self.score),
+            percentiles(&self.survival_time),
+            self.deaths.own_body,
+            self.deaths.other_snake,
+            self.deaths.timeout,
+        )
+    }
+}
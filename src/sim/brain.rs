@@ -0,0 +1,133 @@
+use std::str::FromStr;
+
+use bevy::math::Vec2;
+
+mod cautious;
+mod greedy;
+mod pathfinding;
+
+pub use cautious::CautiousBrain;
+pub use greedy::GreedyBrain;
+pub use pathfinding::PathfindingBrain;
+
+/// Decides where a computer-controlled snake steers. Brains only see a
+/// [`WorldView`], and steer by picking a point the way the cursor does, so they
+/// obey the same turn rate and speed as a player.
+pub trait SnakeBrain: Send + Sync {
+    /// Point to steer toward this tick, or `None` to hold the current heading.
+    fn steer(&mut self, view: &WorldView) -> Option<Vec2>;
+}
+
+/// Built-in strategies, selectable from the settings panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrainKind {
+    /// Heads straight for the nearest coin.
+    Greedy,
+    /// Plans a path to the nearest coin around walls and bodies.
+    Pathfinding,
+    /// Stays in open space and only goes for coins that are safe to reach.
+    Cautious,
+}
+
+impl BrainKind {
+    pub const ALL: [BrainKind; 3] = [
+        BrainKind::Greedy,
+        BrainKind::Pathfinding,
+        BrainKind::Cautious,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BrainKind::Greedy => "Greedy",
+            BrainKind::Pathfinding => "Pathfinding",
+            BrainKind::Cautious => "Cautious",
+        }
+    }
+
+    pub fn cycle(&self, step: i32) -> BrainKind {
+        let index = Self::ALL.iter().position(|kind| kind == self).unwrap_or(0) as i32;
+        Self::ALL[(index + step).rem_euclid(Self::ALL.len() as i32) as usize]
+    }
+
+    pub fn brain(&self) -> Box<dyn SnakeBrain> {
+        match self {
+            BrainKind::Greedy => Box::new(GreedyBrain),
+            BrainKind::Pathfinding => Box::<PathfindingBrain>::default(),
+            BrainKind::Cautious => Box::<CautiousBrain>::default(),
+        }
+    }
+}
+
+impl FromStr for BrainKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<BrainKind, String> {
+        BrainKind::ALL
+            .into_iter()
+            .find(|kind| kind.label().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("unknown bot `{name}`; use greedy, pathfinding or cautious"))
+    }
+}
+
+/// Read-only snapshot of the arena from one snake's point of view.
+#[derive(Debug, Clone, Default)]
+pub struct WorldView {
+    pub head: Vec2,
+    /// Unit direction of travel, or zero before the snake has started moving.
+    pub heading: Vec2,
+    pub speed: f32,
+    /// Radius of every segment, head included.
+    pub snake_radius: f32,
+    /// The snake's own segments after the head, nearest first.
+    pub own_body: Vec<Vec2>,
+    /// Every segment of every other snake, heads included.
+    pub others: Vec<Vec2>,
+    pub coins: Vec<Vec2>,
+    /// The arena spans `-arena_half_size..=arena_half_size`.
+    pub arena_half_size: Vec2,
+}
+
+impl WorldView {
+    /// Own segments this close to the head trail right behind it and can't be hit yet.
+    pub const OWN_BODY_SKIP: usize = 4;
+
+    pub fn nearest_coin(&self) -> Option<Vec2> {
+        self.coins.iter().copied().min_by(|a, b| {
+            a.distance_squared(self.head)
+                .total_cmp(&b.distance_squared(self.head))
+        })
+    }
+
+    /// Segments the head could crash into.
+    pub fn obstacles(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.own_body
+            .iter()
+            .skip(Self::OWN_BODY_SKIP)
+            .chain(self.others.iter())
+            .copied()
+    }
+
+    /// Whether a head at `point` would overlap a body or leave the arena.
+    pub fn is_blocked(&self, point: Vec2, margin: f32) -> bool {
+        let limit = self.arena_half_size - Vec2::splat(self.snake_radius + margin);
+        if point.abs().cmpgt(limit).any() {
+            return true;
+        }
+        let reach = self.snake_radius * 2.0 + margin;
+        self.obstacles()
+            .any(|obstacle| obstacle.distance_squared(point) < reach * reach)
+    }
+
+    /// Free distance from the head along `direction`, up to `max_distance`.
+    pub fn clearance(&self, direction: Vec2, max_distance: f32) -> f32 {
+        let step = self.snake_radius;
+        let mut distance = step;
+        while distance <= max_distance {
+            if self.is_blocked(self.head + direction * distance, 0.0) {
+                return distance - step;
+            }
+            distance += step;
+        }
+        max_distance
+    }
+}
@@ -0,0 +1,69 @@
+use std::f32::consts::PI;
+
+use bevy::math::Vec2;
+
+use super::{SnakeBrain, WorldView};
+
+/// Keeps to open space, and only turns toward a coin when the way there is clear.
+pub struct CautiousBrain {
+    /// Least distance ahead each direction is checked for walls and bodies.
+    pub look_ahead: f32,
+    /// Seconds of travel checked ahead, so faster snakes look further.
+    pub look_ahead_time: f32,
+    /// Free distance a direction needs to be worth taking at all.
+    pub safe_distance: f32,
+    /// Directions checked either side of the heading.
+    pub rays: usize,
+    /// Widest angle checked either side of the heading, in radians.
+    pub spread: f32,
+}
+
+impl Default for CautiousBrain {
+    fn default() -> Self {
+        Self {
+            look_ahead: 300.0,
+            look_ahead_time: 1.0,
+            safe_distance: 120.0,
+            rays: 8,
+            spread: PI * 2.0 / 3.0,
+        }
+    }
+}
+
+impl CautiousBrain {
+    fn look_ahead(&self, view: &WorldView) -> f32 {
+        self.look_ahead.max(view.speed * self.look_ahead_time)
+    }
+
+    /// Most open direction around the heading, preferring ones close to straight on.
+    pub fn open_direction(&self, view: &WorldView) -> Vec2 {
+        let heading = view.heading.try_normalize().unwrap_or(Vec2::X);
+        let rays = self.rays.max(1) as i32;
+        (-rays..=rays)
+            .map(|index| {
+                let angle = self.spread * index as f32 / rays as f32;
+                let direction = Vec2::from_angle(angle).rotate(heading);
+                // A small penalty for turning breaks ties in favour of going straight.
+                let score = view.clearance(direction, self.look_ahead(view)) - angle.abs() * 10.0;
+                (direction, score)
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(direction, _)| direction)
+            .unwrap_or(heading)
+    }
+}
+
+impl SnakeBrain for CautiousBrain {
+    fn steer(&mut self, view: &WorldView) -> Option<Vec2> {
+        if let Some(coin) = view.nearest_coin() {
+            let to_coin = coin - view.head;
+            let distance = to_coin.length();
+            let direction = to_coin.normalize_or_zero();
+            let needed = distance.min(self.safe_distance);
+            if direction != Vec2::ZERO && view.clearance(direction, needed) >= needed {
+                return Some(coin);
+            }
+        }
+        Some(view.head + self.open_direction(view) * self.look_ahead(view))
+    }
+}
@@ -0,0 +1,12 @@
+use bevy::math::Vec2;
+
+use super::{SnakeBrain, WorldView};
+
+/// Heads straight for the nearest coin and ignores everything in the way.
+pub struct GreedyBrain;
+
+impl SnakeBrain for GreedyBrain {
+    fn steer(&mut self, view: &WorldView) -> Option<Vec2> {
+        view.nearest_coin()
+    }
+}
@@ -0,0 +1,580 @@
+use std::thread;
+
+use bevy::math::Vec2;
+
+use super::{BrainKind, DeathCause, Difficulty, Rules, Simulation, SnakeBrain, Steer, WorldView};
+
+/// What the agent sees each step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObservationKind {
+    /// Rays fanned out around the heading, each reporting how close the nearest
+    /// wall, own body, other snake and coin are along it.
+    Rays {
+        count: usize,
+        /// Spread of the fan in radians, centered on the heading.
+        field_of_view: f32,
+        /// Anything further away than this is not seen.
+        max_distance: f32,
+    },
+    /// A square occupancy grid centered on the head and turned so the heading
+    /// points up, with channels for heads, bodies, coins and walls.
+    Grid {
+        /// Cells per side. Odd sizes put the head in the middle of a cell.
+        size: usize,
+        /// World units covered by one cell.
+        cell_size: f32,
+    },
+}
+
+impl ObservationKind {
+    /// Shape of [`Observation::data`] in row-major order.
+    pub fn shape(&self) -> Vec<usize> {
+        match self {
+            ObservationKind::Rays { count, .. } => vec![*count, RAY_CHANNELS],
+            ObservationKind::Grid { size, .. } => vec![GRID_CHANNELS, *size, *size],
+        }
+    }
+}
+
+impl Default for ObservationKind {
+    fn default() -> Self {
+        ObservationKind::Rays {
+            count: 16,
+            field_of_view: std::f32::consts::TAU,
+            max_distance: 600.0,
+        }
+    }
+}
+
+/// Ray channels, in order: wall, own body, other snakes, coin.
+const RAY_CHANNELS: usize = 4;
+/// Grid channels, in order: heads, bodies, coins, walls.
+const GRID_CHANNELS: usize = 4;
+
+/// A flat observation, as a training framework would want to copy it into a tensor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub shape: Vec<usize>,
+    /// Values in `0.0..=1.0`. Rays read 1 right at the head and 0 when nothing
+    /// is in range; grid cells are 1 when occupied.
+    pub data: Vec<f32>,
+}
+
+/// How the agent's snake is steered for one step.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Action {
+    #[default]
+    Straight,
+    Left,
+    Right,
+    /// Turn at a share of the full turn rate, positive to the left.
+    Turn(f32),
+}
+
+impl Action {
+    /// The discrete actions, indexed the way [`Action::from_index`] reads them.
+    pub const DISCRETE: [Action; 3] = [Action::Straight, Action::Left, Action::Right];
+
+    pub fn from_index(index: usize) -> Action {
+        Self::DISCRETE[index % Self::DISCRETE.len()]
+    }
+
+    fn steer(&self) -> Steer {
+        match self {
+            Action::Straight => Steer::Hold,
+            Action::Left => Steer::Turn(1.0),
+            Action::Right => Steer::Turn(-1.0),
+            Action::Turn(share) => Steer::Turn(*share),
+        }
+    }
+}
+
+/// Weights of each part of the reward. Only coins and death count by default.
+#[derive(Debug, Clone)]
+pub struct RewardConfig {
+    /// Per coin eaten.
+    pub coin: f32,
+    /// Once, on the step the snake dies.
+    pub death: f32,
+    /// Per simulated second alive.
+    pub alive_per_second: f32,
+    /// Per world unit the head closes in on the nearest coin, negative when it moves away.
+    pub coin_approach: f32,
+}
+
+impl Default for RewardConfig {
+    fn default() -> Self {
+        Self {
+            coin: 1.0,
+            death: -1.0,
+            alive_per_second: 0.0,
+            coin_approach: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EnvConfig {
+    pub difficulty: Difficulty,
+    pub observation: ObservationKind,
+    pub reward: RewardConfig,
+    /// Simulated seconds per tick.
+    pub tick: f32,
+    /// Ticks each action is held for.
+    pub ticks_per_step: u32,
+    /// Bots sharing the arena with the agent.
+    pub opponents: usize,
+    pub opponent_brain: BrainKind,
+    /// Episodes still going after this many simulated seconds are cut off.
+    pub max_time: f32,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            difficulty: Difficulty::Normal,
+            observation: ObservationKind::default(),
+            reward: RewardConfig::default(),
+            tick: 1.0 / 60.0,
+            ticks_per_step: 4,
+            opponents: 0,
+            opponent_brain: BrainKind::Cautious,
+            max_time: 300.0,
+        }
+    }
+}
+
+/// Extra detail about a step that isn't part of the reward.
+#[derive(Debug, Clone, Default)]
+pub struct StepInfo {
+    /// Coins eaten this episode.
+    pub score: u32,
+    /// Simulated seconds into the episode.
+    pub time: f32,
+    pub death: Option<DeathCause>,
+    /// The episode hit `max_time` rather than ending in a crash.
+    pub truncated: bool,
+    /// The last observation of an episode that [`VecEnv`] reset on its own.
+    pub terminal_observation: Option<Observation>,
+}
+
+/// Result of [`Env::step`]: observation, reward, whether the episode is over, and info.
+pub type Transition = (Observation, f32, bool, StepInfo);
+
+/// A single-agent environment over the headless [`Simulation`]. The agent
+/// plays the first snake; any opponents are bots.
+pub struct Env {
+    pub config: EnvConfig,
+    pub simulation: Simulation,
+    opponents: Vec<Box<dyn SnakeBrain>>,
+    done: bool,
+}
+
+impl Env {
+    /// A new environment, already reset with seed 0.
+    pub fn new(config: EnvConfig) -> Env {
+        let mut env = Env {
+            simulation: Simulation::new(Rules::default(), 0, &[]),
+            opponents: Vec::new(),
+            done: false,
+            config,
+        };
+        env.reset(0);
+        env
+    }
+
+    /// Starts a new episode. The same seed always replays the same coin spawns.
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        let rules = Rules::for_difficulty(self.config.difficulty);
+        self.simulation = Simulation::new(
+            rules,
+            seed,
+            &Simulation::ring_spawns(self.config.opponents + 1),
+        );
+        self.opponents = (0..self.config.opponents)
+            .map(|_| self.config.opponent_brain.brain())
+            .collect();
+        self.done = false;
+        self.observe()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Holds `action` for `ticks_per_step` ticks, stopping early if the episode ends.
+    /// Stepping a finished episode does nothing until the next [`Env::reset`].
+    pub fn step(&mut self, action: Action) -> Transition {
+        if self.done {
+            return (self.observe(), 0.0, true, self.info());
+        }
+        let reward_config = &self.config.reward;
+        let mut reward = 0.0;
+        for _ in 0..self.config.ticks_per_step {
+            let before = nearest_coin_distance(&self.simulation);
+            let mut steering = vec![action.steer()];
+            for (index, brain) in self.opponents.iter_mut().enumerate() {
+                let snake = index + 1;
+                steering.push(if self.simulation.snakes[snake].is_alive() {
+                    brain
+                        .steer(&self.simulation.view(snake))
+                        .map_or(Steer::Hold, Steer::Toward)
+                } else {
+                    Steer::Hold
+                });
+            }
+            let events = self.simulation.step(self.config.tick, &steering);
+
+            let eaten = events.eaten.iter().filter(|index| **index == 0).count();
+            reward += eaten as f32 * reward_config.coin;
+            reward += reward_config.alive_per_second * self.config.tick;
+            // Eating moves the nearest coin elsewhere, which isn't the agent closing in.
+            if eaten == 0 {
+                if let (Some(before), Some(after)) =
+                    (before, nearest_coin_distance(&self.simulation))
+                {
+                    reward += (before - after) * reward_config.coin_approach;
+                }
+            }
+            if !self.simulation.snakes[0].is_alive() {
+                reward += reward_config.death;
+                self.done = true;
+                break;
+            }
+            if self.simulation.time >= self.config.max_time {
+                self.done = true;
+                break;
+            }
+        }
+        (self.observe(), reward, self.done, self.info())
+    }
+
+    pub fn observe(&self) -> Observation {
+        match self.config.observation {
+            ObservationKind::Rays {
+                count,
+                field_of_view,
+                max_distance,
+            } => cast_rays(&self.simulation, count, field_of_view, max_distance),
+            ObservationKind::Grid { size, cell_size } => {
+                rasterize(&self.simulation, size, cell_size)
+            }
+        }
+    }
+
+    fn info(&self) -> StepInfo {
+        let snake = &self.simulation.snakes[0];
+        StepInfo {
+            score: snake.score,
+            time: self.simulation.time,
+            death: snake.death,
+            truncated: snake.is_alive() && self.done,
+            terminal_observation: None,
+        }
+    }
+}
+
+/// Many environments stepped together, split across the available cores.
+/// Finished episodes reset on their own so every step returns a live observation.
+pub struct VecEnv {
+    pub envs: Vec<Env>,
+    /// Seed each environment's next episode starts from.
+    next_seeds: Vec<u64>,
+}
+
+impl VecEnv {
+    pub fn new(config: EnvConfig, count: usize) -> VecEnv {
+        VecEnv {
+            envs: (0..count).map(|_| Env::new(config.clone())).collect(),
+            next_seeds: vec![0; count],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.envs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.envs.is_empty()
+    }
+
+    /// Resets environment `i` with seed `first_seed + i`. Episodes reset
+    /// automatically later on carry on counting up by the number of environments.
+    pub fn reset(&mut self, first_seed: u64) -> Vec<Observation> {
+        let count = self.envs.len() as u64;
+        self.envs
+            .iter_mut()
+            .zip(self.next_seeds.iter_mut())
+            .enumerate()
+            .map(|(index, (env, next_seed))| {
+                let seed = first_seed + index as u64;
+                *next_seed = seed + count;
+                env.reset(seed)
+            })
+            .collect()
+    }
+
+    /// Steps every environment with its own action. `actions` holds one entry
+    /// per environment; missing entries go straight.
+    pub fn step(&mut self, actions: &[Action]) -> Vec<Transition> {
+        let count = self.envs.len() as u64;
+        let threads = thread::available_parallelism().map_or(1, |count| count.get());
+        let chunk_size = self.envs.len().div_ceil(threads).max(1);
+        thread::scope(|scope| {
+            let workers: Vec<_> = self
+                .envs
+                .chunks_mut(chunk_size)
+                .zip(self.next_seeds.chunks_mut(chunk_size))
+                .enumerate()
+                .map(|(chunk, (envs, next_seeds))| {
+                    let first = chunk * chunk_size;
+                    scope.spawn(move || {
+                        envs.iter_mut()
+                            .zip(next_seeds.iter_mut())
+                            .enumerate()
+                            .map(|(offset, (env, next_seed))| {
+                                let action = actions.get(first + offset).copied();
+                                let (observation, reward, done, mut info) =
+                                    env.step(action.unwrap_or_default());
+                                if !done {
+                                    return (observation, reward, done, info);
+                                }
+                                info.terminal_observation = Some(observation);
+                                let observation = env.reset(*next_seed);
+                                *next_seed += count;
+                                (observation, reward, done, info)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("environment thread panicked"))
+                .collect()
+        })
+    }
+}
+
+fn nearest_coin_distance(simulation: &Simulation) -> Option<f32> {
+    let head = simulation.snakes[0].head();
+    simulation
+        .coins
+        .iter()
+        .map(|coin| coin.distance(head))
+        .min_by(f32::total_cmp)
+}
+
+/// Distance along a ray from `origin` in unit `direction` to the edge of a
+/// circle, or `None` if the ray misses it. Zero when starting inside.
+fn ray_circle(origin: Vec2, direction: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+    let offset = center - origin;
+    let along = offset.dot(direction);
+    let miss_squared = offset.length_squared() - along * along;
+    if miss_squared > radius * radius {
+        return None;
+    }
+    let distance = along - (radius * radius - miss_squared).sqrt();
+    if distance >= 0.0 {
+        Some(distance)
+    } else if offset.length_squared() <= radius * radius {
+        Some(0.0)
+    } else {
+        None
+    }
+}
+
+/// Distance along a ray from inside a box centered on the origin to its edge.
+fn ray_box(origin: Vec2, direction: Vec2, half_size: Vec2) -> f32 {
+    let axis = |origin: f32, direction: f32, half_size: f32| {
+        if direction > 0.0 {
+            (half_size - origin) / direction
+        } else if direction < 0.0 {
+            (-half_size - origin) / direction
+        } else {
+            f32::INFINITY
+        }
+    };
+    axis(origin.x, direction.x, half_size.x)
+        .min(axis(origin.y, direction.y, half_size.y))
+        .max(0.0)
+}
+
+fn cast_rays(
+    simulation: &Simulation,
+    count: usize,
+    field_of_view: f32,
+    max_distance: f32,
+) -> Observation {
+    let view = simulation.view(0);
+    let heading = view.heading.try_normalize().unwrap_or(Vec2::X);
+    let radius = view.snake_radius;
+    let wall_limit = view.arena_half_size - Vec2::splat(radius);
+    let own_body: Vec<Vec2> = view
+        .own_body
+        .iter()
+        .skip(WorldView::OWN_BODY_SKIP)
+        .copied()
+        .collect();
+    let nearness = |distance: Option<f32>| {
+        distance
+            .filter(|distance| *distance < max_distance)
+            .map_or(0.0, |distance| 1.0 - distance / max_distance)
+    };
+    let nearest = |direction: Vec2, centers: &[Vec2], reach: f32| {
+        centers
+            .iter()
+            .filter_map(|center| ray_circle(view.head, direction, *center, reach))
+            .min_by(f32::total_cmp)
+    };
+
+    let mut data = Vec::with_capacity(count * RAY_CHANNELS);
+    for ray in 0..count {
+        // A full circle would cast the first and last rays the same way.
+        let spread = if field_of_view >= std::f32::consts::TAU {
+            field_of_view * ray as f32 / count as f32 - field_of_view / 2.0
+        } else if count > 1 {
+            field_of_view * ray as f32 / (count - 1) as f32 - field_of_view / 2.0
+        } else {
+            0.0
+        };
+        let direction = Vec2::from_angle(spread).rotate(heading);
+        data.push(nearness(Some(ray_box(view.head, direction, wall_limit))));
+        data.push(nearness(nearest(direction, &own_body, radius * 2.0)));
+        data.push(nearness(nearest(direction, &view.others, radius * 2.0)));
+        data.push(nearness(nearest(
+            direction,
+            &view.coins,
+            radius + simulation.rules.coin_radius,
+        )));
+    }
+    Observation {
+        shape: vec![count, RAY_CHANNELS],
+        data,
+    }
+}
+
+fn rasterize(simulation: &Simulation, size: usize, cell_size: f32) -> Observation {
+    let agent = &simulation.snakes[0];
+    let head = agent.head();
+    let forward = agent.direction.try_normalize().unwrap_or(Vec2::X);
+    let right = -forward.perp();
+    let center = size as f32 / 2.0;
+    let mut data = vec![0.0; GRID_CHANNELS * size * size];
+
+    // Row 0 is straight ahead of the head, and columns run from left to right.
+    let cell = |point: Vec2| {
+        let offset = point - head;
+        let row = (center - offset.dot(forward) / cell_size).floor();
+        let column = (center + offset.dot(right) / cell_size).floor();
+        let in_grid = (0.0..size as f32).contains(&row) && (0.0..size as f32).contains(&column);
+        in_grid.then_some(row as usize * size + column as usize)
+    };
+    let mut mark = |channel: usize, point: Vec2| {
+        if let Some(index) = cell(point) {
+            data[channel * size * size + index] = 1.0;
+        }
+    };
+
+    for snake in simulation.snakes.iter().filter(|snake| snake.is_alive()) {
+        mark(0, snake.head());
+        for segment in snake.segments.iter().skip(1) {
+            mark(1, segment.position);
+        }
+    }
+    for coin in simulation.coins.iter() {
+        mark(2, *coin);
+    }
+
+    let half_size = simulation.rules.arena_half_size();
+    for row in 0..size {
+        for column in 0..size {
+            let point = head + forward * (center - row as f32 - 0.5) * cell_size
+                - right * (center - column as f32 - 0.5) * cell_size;
+            if point.abs().cmpgt(half_size).any() {
+                data[3 * size * size + row * size + column] = 1.0;
+            }
+        }
+    }
+    Observation {
+        shape: vec![GRID_CHANNELS, size, size],
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The agent at the origin heading right, with another snake above it.
+    fn simulation() -> Simulation {
+        Simulation::new(
+            Rules::default(),
+            0,
+            &[(Vec2::ZERO, Vec2::X), (Vec2::new(0.0, 200.0), Vec2::X)],
+        )
+    }
+
+    #[test]
+    fn rays_see_what_lies_along_them() {
+        let mut simulation = simulation();
+        simulation.coins.push(Vec2::new(100.0, 0.0));
+        let rules = simulation.rules.clone();
+        let max_distance = 10_000.0;
+        let observation = cast_rays(&simulation, 4, std::f32::consts::TAU, max_distance);
+        assert_eq!(observation.shape, [4, RAY_CHANNELS]);
+        let ray = |index: usize| &observation.data[index * RAY_CHANNELS..][..RAY_CHANNELS];
+        let nearness = |distance: f32| 1.0 - distance / max_distance;
+
+        // Rays go behind, right, ahead and left of the heading.
+        let wall = rules.arena_half_size().x - rules.snake_radius;
+        let coin = 100.0 - rules.snake_radius - rules.coin_radius;
+        let other = 200.0 - rules.snake_radius * 2.0;
+        assert!((ray(2)[0] - nearness(wall)).abs() < 1e-5);
+        assert!((ray(2)[3] - nearness(coin)).abs() < 1e-5);
+        assert!((ray(3)[2] - nearness(other)).abs() < 1e-5);
+        assert_eq!(ray(0)[3], 0.0);
+        assert_eq!(ray(1)[2], 0.0);
+        assert!(ray(0)[1..].iter().all(|value| *value == 0.0));
+    }
+
+    #[test]
+    fn rays_ignore_what_is_out_of_range() {
+        let mut simulation = simulation();
+        simulation.coins.push(Vec2::new(100.0, 0.0));
+        let observation = cast_rays(&simulation, 4, std::f32::consts::TAU, 50.0);
+        assert!(observation.data.iter().all(|value| *value == 0.0));
+    }
+
+    #[test]
+    fn grid_is_turned_to_the_heading() {
+        let mut simulation = simulation();
+        // Ahead, and to the right of the head.
+        simulation.coins.push(Vec2::new(30.0, 0.0));
+        simulation.coins.push(Vec2::new(0.0, -30.0));
+        let observation = rasterize(&simulation, 5, 20.0);
+        assert_eq!(observation.shape, [GRID_CHANNELS, 5, 5]);
+        let cell = |channel: usize, row: usize, column: usize| {
+            observation.data[channel * 25 + row * 5 + column]
+        };
+
+        assert_eq!(cell(0, 2, 2), 1.0);
+        assert_eq!(cell(2, 1, 2), 1.0);
+        assert_eq!(cell(2, 2, 4), 1.0);
+        let marked = |channel: usize| observation.data[channel * 25..][..25].iter().sum::<f32>();
+        assert_eq!(marked(0), 1.0);
+        assert_eq!(marked(2), 2.0);
+        assert_eq!(marked(3), 0.0);
+    }
+
+    #[test]
+    fn grid_marks_cells_past_the_wall() {
+        let rules = Rules::default();
+        let edge = rules.arena_half_size().x;
+        let simulation = Simulation::new(rules, 0, &[(Vec2::new(edge - 30.0, 0.0), Vec2::X)]);
+        let observation = rasterize(&simulation, 5, 20.0);
+        let walls = &observation.data[3 * 25..];
+        assert!(walls[..5].iter().all(|value| *value == 1.0));
+        assert!(walls[5..].iter().all(|value| *value == 0.0));
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::VecDeque;
+
+use bevy::math::Vec2;
+
+/// Minimum distance the head travels before a new path point is recorded.
+const PATH_SAMPLE_SPACING: f32 = 2.0;
+
+/// Polyline of recent head positions, newest first. Segments are placed along it at a
+/// fixed arc length behind each other, so spacing doesn't depend on frame rate or speed.
+#[derive(Debug, Clone, Default)]
+pub struct BodyPath(VecDeque<Vec2>);
+
+impl BodyPath {
+    pub fn record(&mut self, head_position: Vec2) {
+        let replace_front = self
+            .0
+            .get(1)
+            .is_some_and(|second| head_position.distance(*second) < PATH_SAMPLE_SPACING);
+        if replace_front {
+            self.0[0] = head_position;
+        } else if self.0.front() != Some(&head_position) {
+            self.0.push_front(head_position);
+        }
+    }
+
+    /// Position and heading `distance` world units behind the head. Falls back to the
+    /// oldest point when the path is shorter than that.
+    pub fn sample(&self, distance: f32) -> Option<(Vec2, Vec2)> {
+        let mut remaining = distance;
+        let mut heading = Vec2::ZERO;
+        for (ahead, behind) in self.0.iter().zip(self.0.iter().skip(1)) {
+            let length = ahead.distance(*behind);
+            heading = (*ahead - *behind).normalize_or_zero();
+            if remaining <= length && length > 0.0 {
+                return Some((ahead.lerp(*behind, remaining / length), heading));
+            }
+            remaining -= length;
+        }
+        self.0.back().map(|oldest| (*oldest, heading))
+    }
+
+    /// Drops points further than `max_length` behind the head.
+    pub fn trim(&mut self, max_length: f32) {
+        let mut length = 0.0;
+        for index in 1..self.0.len() {
+            length += self.0[index - 1].distance(self.0[index]);
+            if length > max_length {
+                self.0.truncate(index + 1);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn straight_path() -> BodyPath {
+        let mut path = BodyPath::default();
+        for x in 0..=10 {
+            path.record(Vec2::new(x as f32 * 10.0, 0.0));
+        }
+        path
+    }
+
+    #[test]
+    fn sample_walks_back_from_the_head() {
+        let path = straight_path();
+        assert_eq!(path.sample(0.0), Some((Vec2::new(100.0, 0.0), Vec2::X)));
+        assert_eq!(path.sample(25.0), Some((Vec2::new(75.0, 0.0), Vec2::X)));
+        // Past the end of the path stays on the oldest point.
+        assert_eq!(path.sample(500.0), Some((Vec2::ZERO, Vec2::X)));
+        assert_eq!(BodyPath::default().sample(10.0), None);
+    }
+
+    #[test]
+    fn close_points_move_the_newest() {
+        let mut path = BodyPath::default();
+        for x in [0.0, 10.0, 10.5, 10.5, 11.0] {
+            path.record(Vec2::new(x, 0.0));
+        }
+        // The newest point follows the head until it is a full spacing past the one before.
+        assert_eq!(path.0, [11.0, 10.0, 0.0].map(|x| Vec2::new(x, 0.0)));
+        path.record(Vec2::new(12.5, 0.0));
+        assert_eq!(path.0.len(), 4);
+    }
+
+    #[test]
+    fn trim_keeps_one_point_past_the_length() {
+        let mut path = straight_path();
+        path.trim(35.0);
+        assert_eq!(path.0.len(), 5);
+        assert_eq!(path.sample(35.0), Some((Vec2::new(65.0, 0.0), Vec2::X)));
+    }
+}
@@ -0,0 +1,100 @@
+//! Line-delimited JSON spoken between the game and an external bot, one
+//! message per line, over a child process's stdin/stdout or a TCP socket.
+//!
+//! Each game is one process or one connection. The game opens with a
+//! [`ServerMessage::Start`], then sends a [`ServerMessage::State`] every tick and
+//! waits up to the deadline for a [`SteerMessage`] echoing that tick. A late or
+//! missing reply keeps steering toward the previous target. The game may close
+//! with a [`ServerMessage::End`]; either way the stream is closed afterwards.
+//!
+//! ```text
+//! > {"type":"start","version":1,"deadline_ms":20}
+//! > {"type":"state","tick":0,"head":[0.0,0.0],"heading":[1.0,0.0],"speed":200.0,...}
+//! < {"tick":0,"target":[310.5,-42.0]}
+//! > {"type":"end","score":12}
+//! ```
+
+use bevy::math::Vec2;
+use serde::{Deserialize, Serialize};
+
+use super::WorldView;
+
+/// Bumped whenever a message changes shape.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Sent by the game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Start {
+        version: u32,
+        /// Milliseconds the game waits for each reply.
+        deadline_ms: u64,
+    },
+    State(StateMessage),
+    End {
+        /// Coins the bot's snake ate this game.
+        score: u32,
+    },
+}
+
+/// The arena from the bot's point of view. Points are `[x, y]` in world units
+/// with the origin in the middle of the arena and `y` pointing up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateMessage {
+    pub tick: u64,
+    pub head: [f32; 2],
+    /// Unit direction of travel.
+    pub heading: [f32; 2],
+    /// World units per second.
+    pub speed: f32,
+    pub snake_radius: f32,
+    /// The bot's own segments after the head, nearest first.
+    pub body: Vec<[f32; 2]>,
+    /// Every segment of every other snake.
+    pub others: Vec<[f32; 2]>,
+    pub coins: Vec<[f32; 2]>,
+    /// The arena spans `-arena_half_size..=arena_half_size`.
+    pub arena_half_size: [f32; 2],
+}
+
+impl StateMessage {
+    pub fn new(tick: u64, view: &WorldView) -> StateMessage {
+        let points = |points: &[Vec2]| points.iter().map(|point| point.to_array()).collect();
+        StateMessage {
+            tick,
+            head: view.head.to_array(),
+            heading: view.heading.to_array(),
+            speed: view.speed,
+            snake_radius: view.snake_radius,
+            body: points(&view.own_body),
+            others: points(&view.others),
+            coins: points(&view.coins),
+            arena_half_size: view.arena_half_size.to_array(),
+        }
+    }
+
+    /// Turns the message back into a view, so clients can run the built-in brains.
+    pub fn view(&self) -> WorldView {
+        let points = |points: &[[f32; 2]]| points.iter().copied().map(Vec2::from).collect();
+        WorldView {
+            head: self.head.into(),
+            heading: self.heading.into(),
+            speed: self.speed,
+            snake_radius: self.snake_radius,
+            own_body: points(&self.body),
+            others: points(&self.others),
+            coins: points(&self.coins),
+            arena_half_size: self.arena_half_size.into(),
+        }
+    }
+}
+
+/// Sent by the bot in reply to a state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SteerMessage {
+    /// The tick of the state being answered.
+    pub tick: u64,
+    /// Point to steer toward, or `null` to hold the current heading.
+    pub target: Option<[f32; 2]>,
+}
@@ -0,0 +1,430 @@
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    process::{Child, Command, Stdio},
+    str::FromStr,
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use bevy::math::Vec2;
+
+use super::{
+    protocol::{ServerMessage, StateMessage, SteerMessage, PROTOCOL_VERSION},
+    BrainKind, SnakeBrain, WorldView,
+};
+
+/// Extra time the first reply gets, for a bot that is still starting up.
+const STARTUP_GRACE: Duration = Duration::from_secs(2);
+/// How long a bot process gets to exit on its own once its game is over.
+const EXIT_GRACE: Duration = Duration::from_millis(200);
+
+/// Where a bot comes from, as written on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BotSpec {
+    /// `greedy`, `pathfinding` or `cautious`.
+    BuiltIn(BrainKind),
+    /// `cmd:<program> [args...]`, started once per game and spoken to over stdio.
+    Command(String),
+    /// `tcp:<host>:<port>`, a bot server connected to once per game.
+    Tcp(String),
+}
+
+impl FromStr for BotSpec {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<BotSpec, String> {
+        if let Some(command) = spec.strip_prefix("cmd:") {
+            if command.split_whitespace().next().is_none() {
+                return Err("`cmd:` needs a program to run".to_string());
+            }
+            return Ok(BotSpec::Command(command.to_string()));
+        }
+        if let Some(address) = spec.strip_prefix("tcp:") {
+            return Ok(BotSpec::Tcp(address.to_string()));
+        }
+        spec.parse().map(BotSpec::BuiltIn).map_err(|_| {
+            format!("unknown bot `{spec}`; use a built-in name, cmd:<program> or tcp:<address>")
+        })
+    }
+}
+
+impl fmt::Display for BotSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BotSpec::BuiltIn(kind) => write!(f, "{}", kind.label().to_lowercase()),
+            BotSpec::Command(command) => write!(f, "cmd:{command}"),
+            BotSpec::Tcp(address) => write!(f, "tcp:{address}"),
+        }
+    }
+}
+
+impl BotSpec {
+    /// Starts a fresh brain for one game.
+    pub fn brain(&self, deadline: Duration) -> io::Result<Box<dyn SnakeBrain>> {
+        Ok(match self {
+            BotSpec::BuiltIn(kind) => kind.brain(),
+            BotSpec::Command(command) => Box::new(RemoteBrain::spawn(command, deadline)?),
+            BotSpec::Tcp(address) => Box::new(RemoteBrain::connect(address, deadline)?),
+        })
+    }
+}
+
+/// How well an external bot kept up with the game.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RemoteStats {
+    /// States sent.
+    pub ticks: u64,
+    /// Ticks with no reply before the deadline.
+    pub missed: u64,
+    /// Lines from the bot that weren't a valid reply.
+    pub invalid: u64,
+    /// The bot closed its end or stopped accepting states.
+    pub disconnected: bool,
+}
+
+enum Transport {
+    Child(Child),
+    Tcp(TcpStream),
+}
+
+/// A brain in another process, steered over the line protocol in
+/// [`super::protocol`]. Each instance plays one game.
+pub struct RemoteBrain {
+    writer: Box<dyn Write + Send + Sync>,
+    // The receiver is only used through `&mut self`; the lock just makes the brain `Sync`.
+    replies: Mutex<Receiver<Result<SteerMessage, String>>>,
+    transport: Transport,
+    deadline: Duration,
+    target: Option<Vec2>,
+    pub stats: RemoteStats,
+}
+
+impl RemoteBrain {
+    /// Runs `command`, split on whitespace, with its stdin and stdout as the
+    /// connection. The bot's stderr goes to ours, for its own logging.
+    pub fn spawn(command: &str, deadline: Duration) -> io::Result<RemoteBrain> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty bot command"))?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        RemoteBrain::start(Box::new(stdin), stdout, Transport::Child(child), deadline)
+    }
+
+    /// Connects to a bot listening on `address`.
+    pub fn connect(address: &str, deadline: Duration) -> io::Result<RemoteBrain> {
+        let address = address.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("no address for `{address}`"),
+            )
+        })?;
+        let stream = TcpStream::connect_timeout(&address, STARTUP_GRACE)?;
+        stream.set_nodelay(true)?;
+        let reader = stream.try_clone()?;
+        let writer = stream.try_clone()?;
+        RemoteBrain::start(Box::new(writer), reader, Transport::Tcp(stream), deadline)
+    }
+
+    fn start(
+        writer: Box<dyn Write + Send + Sync>,
+        reader: impl Read + Send + 'static,
+        transport: Transport,
+        deadline: Duration,
+    ) -> io::Result<RemoteBrain> {
+        let (sender, replies) = mpsc::channel();
+        // Replies are read on their own thread so a silent bot can't block past the deadline.
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let reply = serde_json::from_str(&line).map_err(|_| line);
+                if sender.send(reply).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut brain = RemoteBrain {
+            writer,
+            replies: Mutex::new(replies),
+            transport,
+            deadline,
+            target: None,
+            stats: RemoteStats::default(),
+        };
+        brain.send(&ServerMessage::Start {
+            version: PROTOCOL_VERSION,
+            deadline_ms: deadline.as_millis() as u64,
+        })?;
+        Ok(brain)
+    }
+
+    fn send(&mut self, message: &ServerMessage) -> io::Result<()> {
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()
+    }
+
+    /// Tells the bot its final score. The connection closes when the brain is dropped.
+    pub fn finish(&mut self, score: u32) {
+        if !self.stats.disconnected && self.send(&ServerMessage::End { score }).is_err() {
+            self.stats.disconnected = true;
+        }
+    }
+
+    /// Waits for the reply to `tick` until the deadline. Replies to older ticks
+    /// arrived too late and are dropped.
+    fn receive(&mut self, tick: u64) -> Option<SteerMessage> {
+        let deadline = if tick == 0 {
+            self.deadline + STARTUP_GRACE
+        } else {
+            self.deadline
+        };
+        let give_up = Instant::now() + deadline;
+        let replies = self
+            .replies
+            .get_mut()
+            .unwrap_or_else(|error| error.into_inner());
+        loop {
+            let remaining = give_up.saturating_duration_since(Instant::now());
+            match replies.recv_timeout(remaining) {
+                Ok(Ok(reply)) if reply.tick == tick => return Some(reply),
+                Ok(Ok(_)) => {}
+                Ok(Err(_)) => self.stats.invalid += 1,
+                Err(RecvTimeoutError::Timeout) => return None,
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.stats.disconnected = true;
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+impl SnakeBrain for RemoteBrain {
+    fn steer(&mut self, view: &WorldView) -> Option<Vec2> {
+        if self.stats.disconnected {
+            return self.target;
+        }
+        let tick = self.stats.ticks;
+        self.stats.ticks += 1;
+        if self
+            .send(&ServerMessage::State(StateMessage::new(tick, view)))
+            .is_err()
+        {
+            self.stats.disconnected = true;
+            return self.target;
+        }
+        match self.receive(tick) {
+            Some(reply) => self.target = reply.target.map(Vec2::from),
+            None => self.stats.missed += 1,
+        }
+        self.target
+    }
+}
+
+impl Drop for RemoteBrain {
+    fn drop(&mut self) {
+        // Closing our end is the bot's cue that the game is over.
+        self.writer = Box::new(io::sink());
+        match &mut self.transport {
+            Transport::Child(child) => {
+                let give_up = Instant::now() + EXIT_GRACE;
+                while matches!(child.try_wait(), Ok(None)) && Instant::now() < give_up {
+                    thread::sleep(Duration::from_millis(5));
+                }
+                if matches!(child.try_wait(), Ok(None)) {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+            }
+            Transport::Tcp(stream) => {
+                let _ = stream.shutdown(std::net::Shutdown::Both);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, net::TcpListener, path::PathBuf};
+
+    use super::*;
+    use crate::sim::Steer;
+
+    const DEADLINE: Duration = Duration::from_millis(100);
+
+    fn view() -> WorldView {
+        WorldView {
+            head: Vec2::ZERO,
+            heading: Vec2::X,
+            speed: 200.0,
+            snake_radius: 10.0,
+            own_body: vec![Vec2::new(-8.0, 0.0)],
+            others: Vec::new(),
+            coins: vec![Vec2::new(50.0, 0.0)],
+            arena_half_size: Vec2::new(1200.0, 800.0),
+        }
+    }
+
+    /// One tick of play, as the game turns the brain's answer into steering.
+    fn steer(brain: &mut RemoteBrain) -> Steer {
+        brain.steer(&view()).map_or(Steer::Hold, Steer::Toward)
+    }
+
+    /// Reads the next message on the bot's side and checks it is of `kind`.
+    fn expect(reader: &mut impl BufRead, kind: &str) -> ServerMessage {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let message: ServerMessage = serde_json::from_str(&line).unwrap();
+        let read = match message {
+            ServerMessage::Start { .. } => "start",
+            ServerMessage::State(_) => "state",
+            ServerMessage::End { .. } => "end",
+        };
+        assert_eq!(read, kind, "{line}");
+        message
+    }
+
+    #[test]
+    fn tcp_bot_replies_steer_and_late_or_garbled_lines_are_skipped() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let bot = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            assert_eq!(
+                expect(&mut reader, "start"),
+                ServerMessage::Start {
+                    version: PROTOCOL_VERSION,
+                    deadline_ms: 100,
+                }
+            );
+
+            expect(&mut reader, "state");
+            writer
+                .write_all(b"{\"tick\":0,\"target\":[1.5,-2.0]}\n")
+                .unwrap();
+            // Garbage is counted and skipped; the real reply still counts.
+            expect(&mut reader, "state");
+            writer.write_all(b"steer left please\n").unwrap();
+            writer
+                .write_all(b"{\"tick\":1,\"target\":[30.0,4.0]}\n")
+                .unwrap();
+            // Too slow: the game moves on and this answer is thrown away later.
+            expect(&mut reader, "state");
+            thread::sleep(DEADLINE * 2);
+            writer
+                .write_all(b"{\"tick\":2,\"target\":[9.0,9.0]}\n")
+                .unwrap();
+            expect(&mut reader, "state");
+            writer.write_all(b"{\"tick\":3,\"target\":null}\n").unwrap();
+            expect(&mut reader, "end");
+        });
+
+        let mut brain = RemoteBrain::connect(&address, DEADLINE).unwrap();
+        assert_eq!(steer(&mut brain), Steer::Toward(Vec2::new(1.5, -2.0)));
+        assert_eq!(steer(&mut brain), Steer::Toward(Vec2::new(30.0, 4.0)));
+        // A missed tick keeps the last target rather than guessing.
+        assert_eq!(steer(&mut brain), Steer::Toward(Vec2::new(30.0, 4.0)));
+        // Let the late answer arrive before the next tick, which must skip it.
+        thread::sleep(DEADLINE * 2);
+        assert_eq!(steer(&mut brain), Steer::Hold);
+        brain.finish(3);
+        bot.join().unwrap();
+        assert_eq!(
+            brain.stats,
+            RemoteStats {
+                ticks: 4,
+                missed: 1,
+                invalid: 1,
+                disconnected: false,
+            }
+        );
+    }
+
+    #[test]
+    fn silent_bot_holds_its_heading() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let bot = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            expect(&mut reader, "start");
+            expect(&mut reader, "state");
+            expect(&mut reader, "state");
+        });
+
+        let mut brain = RemoteBrain::connect(&address, Duration::from_millis(10)).unwrap();
+        let started = Instant::now();
+        assert_eq!(steer(&mut brain), Steer::Hold);
+        // The first tick waits out the startup grace, later ones only the deadline.
+        assert!(started.elapsed() >= STARTUP_GRACE);
+        let started = Instant::now();
+        assert_eq!(steer(&mut brain), Steer::Hold);
+        assert!(started.elapsed() < STARTUP_GRACE);
+        bot.join().unwrap();
+        assert_eq!(brain.stats.missed, 2);
+    }
+
+    /// A shell script standing in for a bot program, removed again when dropped.
+    struct TempBot(PathBuf);
+
+    impl TempBot {
+        fn new(name: &str, script: &str) -> TempBot {
+            let path =
+                std::env::temp_dir().join(format!("bevy-snake-{name}-{}.sh", std::process::id()));
+            fs::write(&path, script).unwrap();
+            TempBot(path)
+        }
+    }
+
+    impl Drop for TempBot {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn child_process_bots_talk_over_stdio() {
+        // Skips the start line, answers the first state with junk and then a
+        // target, and hangs up after the second.
+        let script = TempBot::new(
+            "stdio-bot",
+            "read start\n\
+             read state\n\
+             echo '{not json'\n\
+             echo '{\"tick\":0,\"target\":[-4.0,12.5]}'\n\
+             read state\n\
+             echo '{\"tick\":1,\"target\":null}'\n",
+        );
+        let command = format!("sh {}", script.0.display());
+        let mut brain = RemoteBrain::spawn(&command, DEADLINE).unwrap();
+        assert_eq!(steer(&mut brain), Steer::Toward(Vec2::new(-4.0, 12.5)));
+        assert_eq!(steer(&mut brain), Steer::Hold);
+        // The bot has exited, so the game keeps the last answer from here on.
+        assert_eq!(steer(&mut brain), Steer::Hold);
+        assert!(brain.stats.disconnected);
+        assert_eq!(brain.stats.invalid, 1);
+    }
+}
@@ -0,0 +1,194 @@
+use std::str::FromStr;
+
+use bevy::math::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// Size of the playfield, centered on the origin.
+pub const ARENA_SIZE: Vec2 = Vec2::new(2400.0, 1600.0);
+/// Head speed a snake starts each round with.
+pub const START_SPEED: f32 = 200.0;
+/// Speed gained per segment eaten.
+pub const SPEED_PER_SEGMENT: f32 = 10.0;
+pub const SNAKE_RADIUS: f32 = 10.0;
+pub const COIN_RADIUS: f32 = 10.0;
+/// Seconds between coin spawns.
+pub const COIN_INTERVAL: f32 = 1.0;
+/// Distance along the head's path between two neighbouring segments.
+pub const SEGMENT_SPACING: f32 = 8.0;
+/// Seconds a new segment can be passed through before it becomes solid.
+pub const NEW_SEGMENT_GRACE: f32 = 2.0;
+/// How far a head must overlap its own body to crash, so the segments right
+/// behind it on a tight turn don't count.
+pub const SELF_OVERLAP: f32 = 7.0;
+/// How far a head must overlap another snake to crash into it.
+pub const OTHER_OVERLAP: f32 = 6.0;
+
+/// Tunable numbers behind the game rules. The defaults are the ones the game plays with.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    pub arena_size: Vec2,
+    pub start_speed: f32,
+    pub speed_per_segment: f32,
+    pub snake_radius: f32,
+    pub coin_radius: f32,
+    pub coin_interval: f32,
+    /// Closest a coin spawns to the arena edge.
+    pub coin_margin: f32,
+    pub segment_spacing: f32,
+    pub new_segment_grace: f32,
+    /// Turning limits every snake starts a round with.
+    pub turning: Turning,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            arena_size: ARENA_SIZE,
+            start_speed: START_SPEED,
+            speed_per_segment: SPEED_PER_SEGMENT,
+            snake_radius: SNAKE_RADIUS,
+            coin_radius: COIN_RADIUS,
+            coin_interval: COIN_INTERVAL,
+            coin_margin: 30.0,
+            segment_spacing: SEGMENT_SPACING,
+            new_segment_grace: NEW_SEGMENT_GRACE,
+            turning: Turning::default(),
+        }
+    }
+}
+
+/// Turning limits for a head, so it swings toward its target instead of snapping.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Turning {
+    /// Fastest the head can rotate, in radians per second.
+    pub max_turn_rate: f32,
+    /// Share of the turn rate lost per body segment, so long snakes turn wider.
+    pub length_turn_falloff: f32,
+    pub min_turn_rate: f32,
+    /// Target distance from the head below which the heading is left alone.
+    pub dead_zone: f32,
+}
+
+impl Default for Turning {
+    fn default() -> Self {
+        Self {
+            max_turn_rate: 6.0,
+            length_turn_falloff: 0.02,
+            min_turn_rate: 2.5,
+            dead_zone: 15.0,
+        }
+    }
+}
+
+impl Turning {
+    /// Turn rate in radians per second for a snake of `length` segments.
+    pub fn turn_rate(&self, length: usize) -> f32 {
+        (self.max_turn_rate / (1.0 + self.length_turn_falloff * length as f32))
+            .max(self.min_turn_rate)
+    }
+}
+
+impl Rules {
+    pub fn for_difficulty(difficulty: Difficulty) -> Rules {
+        let rules = Rules::default();
+        match difficulty {
+            Difficulty::Easy => Rules {
+                speed_per_segment: rules.speed_per_segment / 2.0,
+                coin_interval: rules.coin_interval * 0.75,
+                ..rules
+            },
+            Difficulty::Normal => rules,
+            Difficulty::Hard => Rules {
+                start_speed: rules.start_speed * 1.3,
+                speed_per_segment: rules.speed_per_segment * 1.5,
+                coin_interval: rules.coin_interval * 1.5,
+                ..rules
+            },
+        }
+    }
+
+    /// The same rules played on the arena of `level`.
+    pub fn with_level(self, level: Level) -> Rules {
+        Rules {
+            arena_size: level.arena_size(),
+            ..self
+        }
+    }
+
+    pub fn arena_half_size(&self) -> Vec2 {
+        self.arena_size / 2.0
+    }
+}
+
+/// Presets that scale speed growth and coin spawns from the game's own rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Difficulty, String> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.label().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("unknown difficulty `{name}`; use easy, normal or hard"))
+    }
+}
+
+/// Arena presets: the same rules on a tighter or roomier playfield. `Normal`
+/// is the arena the game plays in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Small,
+    Normal,
+    Large,
+}
+
+impl Level {
+    pub const ALL: [Level; 3] = [Level::Small, Level::Normal, Level::Large];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Level::Small => "small",
+            Level::Normal => "normal",
+            Level::Large => "large",
+        }
+    }
+
+    pub fn arena_size(&self) -> Vec2 {
+        match self {
+            Level::Small => Vec2::new(1600.0, 1200.0),
+            Level::Normal => ARENA_SIZE,
+            Level::Large => ARENA_SIZE * 1.5,
+        }
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Level, String> {
+        Level::ALL
+            .into_iter()
+            .find(|level| level.label().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("unknown level `{name}`; use small, normal or large"))
+    }
+}
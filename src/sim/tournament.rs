@@ -0,0 +1,346 @@
+use std::{borrow::Cow, time::Duration};
+
+use bevy::math::Vec2;
+use serde::Serialize;
+
+use super::{
+    remote::{BotSpec, RemoteBrain},
+    Difficulty, Rules, Simulation, SnakeBrain, Steer,
+};
+
+/// A round robin where every pair of entrants plays the same seeds head to head.
+#[derive(Debug, Clone)]
+pub struct TournamentConfig {
+    /// Games per pairing. Seeds run from zero, and the entrants swap starting
+    /// spots every game.
+    pub games: u64,
+    pub difficulty: Difficulty,
+    /// Simulated seconds per tick.
+    pub tick: f32,
+    /// Games still going after this many simulated seconds go to the higher score.
+    pub max_time: f32,
+    /// How long each external bot gets to answer a tick.
+    pub deadline: Duration,
+}
+
+impl Default for TournamentConfig {
+    fn default() -> Self {
+        Self {
+            games: 10,
+            difficulty: Difficulty::Normal,
+            tick: 1.0 / 60.0,
+            max_time: 120.0,
+            deadline: Duration::from_millis(20),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Entrant {
+    pub name: String,
+    pub spec: BotSpec,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchResult {
+    pub seed: u64,
+    /// Entrant names, in starting-spot order.
+    pub players: [String; 2],
+    pub scores: [u32; 2],
+    /// Index into `players`, or `None` for a draw.
+    pub winner: Option<usize>,
+    /// Simulated seconds the game lasted.
+    pub time: f32,
+    /// External bots that failed to start, and why.
+    pub errors: Vec<String>,
+}
+
+impl MatchResult {
+    pub const CSV_HEADER: &'static str = "seed,player_1,player_2,score_1,score_2,winner,time";
+
+    pub fn csv_row(&self) -> String {
+        let winner = self.winner.map_or("draw", |side| &self.players[side]);
+        format!(
+            "{},{},{},{},{},{},{:.2}",
+            self.seed,
+            csv_field(&self.players[0]),
+            csv_field(&self.players[1]),
+            self.scores[0],
+            self.scores[1],
+            csv_field(winner),
+            self.time,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Standing {
+    pub name: String,
+    pub bot: String,
+    pub played: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// Three per win and one per draw.
+    pub points: u32,
+    pub coins: u32,
+    /// Ticks an external bot didn't answer in time.
+    pub missed_ticks: u64,
+    pub invalid_lines: u64,
+    /// Games where the bot hung up or never started.
+    pub disconnects: u32,
+}
+
+impl Standing {
+    pub const CSV_HEADER: &'static str =
+        "name,bot,played,wins,draws,losses,points,coins,missed_ticks,invalid_lines,disconnects";
+
+    pub fn csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            csv_field(&self.name),
+            csv_field(&self.bot),
+            self.played,
+            self.wins,
+            self.draws,
+            self.losses,
+            self.points,
+            self.coins,
+            self.missed_ticks,
+            self.invalid_lines,
+            self.disconnects,
+        )
+    }
+}
+
+/// Quotes a field that holds a comma, quote or line break, doubling any quotes
+/// inside. Names and bot commands come from the command line and can hold any of them.
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TournamentResult {
+    /// Best first: by points, then coins.
+    pub standings: Vec<Standing>,
+    pub games: Vec<MatchResult>,
+}
+
+/// One side of a game.
+enum Seat {
+    BuiltIn(Box<dyn SnakeBrain>),
+    Remote(RemoteBrain),
+    /// The bot couldn't be started, so the snake just holds its heading.
+    Failed,
+}
+
+impl Seat {
+    fn open(spec: &BotSpec, deadline: Duration) -> Result<Seat, String> {
+        let seat = match spec {
+            BotSpec::BuiltIn(kind) => Seat::BuiltIn(kind.brain()),
+            BotSpec::Command(command) => Seat::Remote(
+                RemoteBrain::spawn(command, deadline)
+                    .map_err(|error| format!("{spec}: {error}"))?,
+            ),
+            BotSpec::Tcp(address) => Seat::Remote(
+                RemoteBrain::connect(address, deadline)
+                    .map_err(|error| format!("{spec}: {error}"))?,
+            ),
+        };
+        Ok(seat)
+    }
+
+    fn steer(&mut self, simulation: &Simulation, index: usize) -> Option<Vec2> {
+        let view = simulation.view(index);
+        match self {
+            Seat::BuiltIn(brain) => brain.steer(&view),
+            Seat::Remote(brain) => brain.steer(&view),
+            Seat::Failed => None,
+        }
+    }
+}
+
+/// Plays every pairing in turn. Games run one at a time so external bots get
+/// the whole machine while they think.
+pub fn run_tournament(config: &TournamentConfig, entrants: &[Entrant]) -> TournamentResult {
+    let mut standings: Vec<Standing> = entrants
+        .iter()
+        .map(|entrant| Standing {
+            name: entrant.name.clone(),
+            bot: entrant.spec.to_string(),
+            ..Standing::default()
+        })
+        .collect();
+    let mut games = Vec::new();
+
+    for first in 0..entrants.len() {
+        for second in first + 1..entrants.len() {
+            for seed in 0..config.games {
+                let sides = if seed % 2 == 0 {
+                    [first, second]
+                } else {
+                    [second, first]
+                };
+                let (result, seats) = play_game(config, entrants, sides, seed);
+                for (side, (entrant, seat)) in sides.iter().zip(seats).enumerate() {
+                    let standing = &mut standings[*entrant];
+                    standing.played += 1;
+                    standing.coins += result.scores[side];
+                    match result.winner {
+                        Some(winner) if winner == side => {
+                            standing.wins += 1;
+                            standing.points += 3;
+                        }
+                        Some(_) => standing.losses += 1,
+                        None => {
+                            standing.draws += 1;
+                            standing.points += 1;
+                        }
+                    }
+                    match seat {
+                        Seat::Remote(brain) => {
+                            standing.missed_ticks += brain.stats.missed;
+                            standing.invalid_lines += brain.stats.invalid;
+                            standing.disconnects += brain.stats.disconnected as u32;
+                        }
+                        Seat::Failed => standing.disconnects += 1,
+                        Seat::BuiltIn(_) => {}
+                    }
+                }
+                games.push(result);
+            }
+        }
+    }
+
+    standings.sort_by(|a, b| b.points.cmp(&a.points).then(b.coins.cmp(&a.coins)));
+    TournamentResult { standings, games }
+}
+
+fn play_game(
+    config: &TournamentConfig,
+    entrants: &[Entrant],
+    sides: [usize; 2],
+    seed: u64,
+) -> (MatchResult, [Seat; 2]) {
+    let mut errors = Vec::new();
+    let mut seats = sides.map(|entrant| {
+        Seat::open(&entrants[entrant].spec, config.deadline).unwrap_or_else(|error| {
+            errors.push(error);
+            Seat::Failed
+        })
+    });
+    let rules = Rules::for_difficulty(config.difficulty);
+    let mut simulation = Simulation::new(rules, seed, &Simulation::ring_spawns(2));
+
+    while simulation.alive() == 2 && simulation.time < config.max_time {
+        let steering: Vec<Steer> = seats
+            .iter_mut()
+            .enumerate()
+            .map(|(index, seat)| {
+                seat.steer(&simulation, index)
+                    .map_or(Steer::Hold, Steer::Toward)
+            })
+            .collect();
+        simulation.step(config.tick, &steering);
+    }
+
+    let scores = [simulation.snakes[0].score, simulation.snakes[1].score];
+    for (seat, score) in seats.iter_mut().zip(scores) {
+        if let Seat::Remote(brain) = seat {
+            brain.finish(score);
+        }
+    }
+    // A bot that never started forfeits. Otherwise a crash loses outright, and a
+    // head-on crash or a timeout with equal scores is a draw.
+    let failed = seats.each_ref().map(|seat| matches!(seat, Seat::Failed));
+    let alive = [
+        simulation.snakes[0].is_alive(),
+        simulation.snakes[1].is_alive(),
+    ];
+    let winner = match (failed, alive) {
+        ([true, false], _) => Some(1),
+        ([false, true], _) => Some(0),
+        ([true, true], _) => None,
+        (_, [true, false]) => Some(0),
+        (_, [false, true]) => Some(1),
+        (_, [true, true]) if scores[0] != scores[1] => {
+            Some(if scores[0] > scores[1] { 0 } else { 1 })
+        }
+        _ => None,
+    };
+    let result = MatchResult {
+        seed,
+        players: sides.map(|entrant| entrants[entrant].name.clone()),
+        scores,
+        winner,
+        time: simulation.time,
+        errors,
+    };
+    (result, seats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standing(name: &str, bot: &str) -> Standing {
+        Standing {
+            name: name.to_string(),
+            bot: bot.to_string(),
+            played: 2,
+            wins: 1,
+            draws: 0,
+            losses: 1,
+            points: 3,
+            coins: 7,
+            missed_ticks: 0,
+            invalid_lines: 0,
+            disconnects: 0,
+        }
+    }
+
+    #[test]
+    fn plain_fields_are_left_alone() {
+        assert_eq!(
+            standing("mine", "cmd:target/release/snake-bot").csv_row(),
+            "mine,cmd:target/release/snake-bot,2,1,0,1,3,7,0,0,0"
+        );
+    }
+
+    #[test]
+    fn fields_with_commas_quotes_or_newlines_are_quoted() {
+        assert_eq!(
+            standing("a,b", "cmd:bot --say \"hi\"").csv_row(),
+            "\"a,b\",\"cmd:bot --say \"\"hi\"\"\",2,1,0,1,3,7,0,0,0"
+        );
+        assert!(standing("two\nlines", "greedy")
+            .csv_row()
+            .starts_with("\"two\nlines\",greedy,"));
+    }
+
+    #[test]
+    fn per_game_rows_quote_names_and_the_winner() {
+        let game = MatchResult {
+            seed: 4,
+            players: ["a,b".to_string(), "say \"hi\"".to_string()],
+            scores: [5, 2],
+            winner: Some(0),
+            time: 12.5,
+            errors: Vec::new(),
+        };
+        assert_eq!(
+            game.csv_row(),
+            "4,\"a,b\",\"say \"\"hi\"\"\",5,2,\"a,b\",12.50"
+        );
+        let draw = MatchResult {
+            winner: None,
+            players: ["greedy".to_string(), "cautious".to_string()],
+            ..game
+        };
+        assert_eq!(draw.csv_row(), "4,greedy,cautious,5,2,draw,12.50");
+    }
+}
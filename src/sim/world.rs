@@ -0,0 +1,454 @@
+use bevy::math::Vec2;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{
+    rules::{OTHER_OVERLAP, SELF_OVERLAP},
+    BodyPath, Rules, Turning, WorldView,
+};
+
+/// How a snake is steered for one tick.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Steer {
+    /// Keep the current heading.
+    #[default]
+    Hold,
+    /// Turn toward a point, as the cursor and bots do.
+    Toward(Vec2),
+    /// Turn at a share of the full turn rate, positive to the left, as the keyboard does.
+    Turn(f32),
+}
+
+/// Why a snake died in the simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeathCause {
+    /// The head ran into the snake's own body.
+    OwnBody,
+    /// The head ran into the snake at this index.
+    Snake(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub position: Vec2,
+    pub heading: Vec2,
+    /// Seconds left before a new segment becomes solid.
+    grace: f32,
+}
+
+impl Segment {
+    pub fn collides(&self) -> bool {
+        self.grace <= 0.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SimSnake {
+    /// Head first.
+    pub segments: Vec<Segment>,
+    /// Unit direction of travel.
+    pub direction: Vec2,
+    pub speed: f32,
+    /// Coins eaten.
+    pub score: u32,
+    /// Seconds the snake has been alive.
+    pub age: f32,
+    pub death: Option<DeathCause>,
+    path: BodyPath,
+}
+
+impl SimSnake {
+    fn new(position: Vec2, direction: Vec2, speed: f32) -> SimSnake {
+        let direction = direction.try_normalize().unwrap_or(Vec2::X);
+        let mut path = BodyPath::default();
+        path.record(position);
+        SimSnake {
+            segments: vec![Segment {
+                position,
+                heading: direction,
+                grace: 0.0,
+            }],
+            direction,
+            speed,
+            score: 0,
+            age: 0.0,
+            death: None,
+            path,
+        }
+    }
+
+    pub fn head(&self) -> Vec2 {
+        self.segments[0].position
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.death.is_none()
+    }
+}
+
+/// What happened during one [`Simulation::step`].
+#[derive(Debug, Clone, Default)]
+pub struct StepEvents {
+    /// Index of each snake that ate a coin, once per coin.
+    pub eaten: Vec<usize>,
+    pub deaths: Vec<(usize, DeathCause)>,
+}
+
+/// The game rules without rendering, input or audio: snakes move, turn, grow
+/// and crash the way they do in the game, and coins spawn from a seeded RNG so a
+/// run can be repeated exactly. Boost, power-ups and combos are left out.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub rules: Rules,
+    pub snakes: Vec<SimSnake>,
+    pub coins: Vec<Vec2>,
+    /// Seconds simulated so far.
+    pub time: f32,
+    coin_timer: f32,
+    rng: StdRng,
+}
+
+impl Simulation {
+    /// Starts a round with one snake per `(position, direction)` spawn.
+    pub fn new(rules: Rules, seed: u64, spawns: &[(Vec2, Vec2)]) -> Simulation {
+        let snakes = spawns
+            .iter()
+            .map(|(position, direction)| SimSnake::new(*position, *direction, rules.start_speed))
+            .collect();
+        Simulation {
+            rules,
+            snakes,
+            coins: Vec::new(),
+            time: 0.0,
+            coin_timer: 0.0,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// `count` spawns on a ring around the centre, facing inward, with a single
+    /// snake starting in the middle.
+    pub fn ring_spawns(count: usize) -> Vec<(Vec2, Vec2)> {
+        if count == 1 {
+            return vec![(Vec2::ZERO, Vec2::X)];
+        }
+        (0..count)
+            .map(|index| {
+                let angle = std::f32::consts::TAU * index as f32 / count as f32;
+                let position = Vec2::from_angle(angle) * 500.0;
+                (position, -position.normalize())
+            })
+            .collect()
+    }
+
+    pub fn alive(&self) -> usize {
+        self.snakes.iter().filter(|snake| snake.is_alive()).count()
+    }
+
+    /// The arena as snake `index` sees it.
+    pub fn view(&self, index: usize) -> WorldView {
+        let snake = &self.snakes[index];
+        WorldView {
+            head: snake.head(),
+            heading: snake.direction,
+            speed: snake.speed,
+            snake_radius: self.rules.snake_radius,
+            own_body: snake
+                .segments
+                .iter()
+                .skip(1)
+                .map(|segment| segment.position)
+                .collect(),
+            others: self
+                .snakes
+                .iter()
+                .enumerate()
+                .filter(|(other, other_snake)| *other != index && other_snake.is_alive())
+                .flat_map(|(_, other_snake)| other_snake.segments.iter().map(|s| s.position))
+                .collect(),
+            coins: self.coins.clone(),
+            arena_half_size: self.rules.arena_half_size(),
+        }
+    }
+
+    /// Advances the round by `delta` seconds. `steering` holds one entry per snake;
+    /// missing entries hold their heading.
+    pub fn step(&mut self, delta: f32, steering: &[Steer]) -> StepEvents {
+        let mut events = StepEvents::default();
+        self.time += delta;
+
+        for (index, snake) in self.snakes.iter_mut().enumerate() {
+            if !snake.is_alive() {
+                continue;
+            }
+            let steer = steering.get(index).copied().unwrap_or_default();
+            move_snake(snake, steer, &self.rules, delta);
+        }
+
+        self.eat_coins(&mut events);
+
+        for snake in self.snakes.iter_mut() {
+            for segment in snake.segments.iter_mut() {
+                segment.grace -= delta;
+            }
+        }
+
+        // Everyone is checked against the same frame, so head-on crashes kill both.
+        let deaths: Vec<(usize, DeathCause)> = (0..self.snakes.len())
+            .filter_map(|index| self.collision(index).map(|cause| (index, cause)))
+            .collect();
+        for (index, cause) in deaths.iter() {
+            self.snakes[*index].death = Some(*cause);
+        }
+        events.deaths = deaths;
+
+        self.coin_timer += delta;
+        while self.coin_timer >= self.rules.coin_interval {
+            self.coin_timer -= self.rules.coin_interval;
+            let half_size = self.rules.arena_half_size() - Vec2::splat(self.rules.coin_margin);
+            self.coins.push(Vec2::new(
+                self.rng.gen_range(-half_size.x..half_size.x),
+                self.rng.gen_range(-half_size.y..half_size.y),
+            ));
+        }
+        events
+    }
+
+    fn eat_coins(&mut self, events: &mut StepEvents) {
+        let reach = self.rules.snake_radius + self.rules.coin_radius;
+        for (index, snake) in self.snakes.iter_mut().enumerate() {
+            if !snake.is_alive() {
+                continue;
+            }
+            let head = snake.head();
+            let before = self.coins.len();
+            self.coins.retain(|coin| coin.distance(head) >= reach);
+            for _ in self.coins.len()..before {
+                // New segments start on the tail and slide back as the path grows.
+                let tail = snake.segments.last().cloned().unwrap();
+                snake.segments.push(Segment {
+                    grace: self.rules.new_segment_grace,
+                    ..tail
+                });
+                snake.speed += self.rules.speed_per_segment;
+                snake.score += 1;
+                events.eaten.push(index);
+            }
+        }
+    }
+
+    fn collision(&self, index: usize) -> Option<DeathCause> {
+        let snake = &self.snakes[index];
+        if !snake.is_alive() {
+            return None;
+        }
+        head_collision(
+            &self.rules,
+            snake.head(),
+            snake
+                .segments
+                .iter()
+                .skip(1)
+                .map(|segment| (segment.position, segment.collides())),
+            self.snakes
+                .iter()
+                .enumerate()
+                .filter(|(other, other_snake)| *other != index && other_snake.is_alive())
+                .map(|(other, other_snake)| {
+                    (
+                        other,
+                        other_snake.segments.iter().map(|segment| segment.position),
+                    )
+                }),
+        )
+    }
+}
+
+/// What a head at `head` crashes into, if anything. `own_body` is the rest of
+/// its snake with whether each segment is solid yet, and `others` is every other
+/// snake's segments by index. Running into another snake counts before running
+/// into your own body.
+pub fn head_collision<Others, Body>(
+    rules: &Rules,
+    head: Vec2,
+    own_body: impl IntoIterator<Item = (Vec2, bool)>,
+    others: Others,
+) -> Option<DeathCause>
+where
+    Others: IntoIterator<Item = (usize, Body)>,
+    Body: IntoIterator<Item = Vec2>,
+{
+    let radius = rules.snake_radius;
+    for (other, body) in others {
+        if body
+            .into_iter()
+            .any(|position| head.distance(position) < radius * 2.0 - OTHER_OVERLAP)
+        {
+            return Some(DeathCause::Snake(other));
+        }
+    }
+    let hit_self = own_body
+        .into_iter()
+        .any(|(position, solid)| solid && head.distance(position) + SELF_OVERLAP < radius);
+    hit_self.then_some(DeathCause::OwnBody)
+}
+
+/// Heading after steering for `delta` seconds. Longer snakes turn wider. A zero
+/// `direction` is a snake waiting for its first input.
+pub fn steer_heading(
+    turning: &Turning,
+    head: Vec2,
+    direction: Vec2,
+    length: usize,
+    steer: Steer,
+    delta: f32,
+) -> Vec2 {
+    let max_turn = turning.turn_rate(length) * delta;
+    let turn = match steer {
+        // A snake that hasn't started moving faces its first target at once.
+        Steer::Toward(target)
+            if direction == Vec2::ZERO && (target - head).length() > turning.dead_zone =>
+        {
+            return (target - head).normalize_or_zero();
+        }
+        Steer::Hold => 0.0,
+        Steer::Toward(target) if (target - head).length() > turning.dead_zone => direction
+            .angle_between((target - head).normalize_or_zero())
+            .clamp(-max_turn, max_turn),
+        Steer::Toward(_) => 0.0,
+        Steer::Turn(share) => share.clamp(-1.0, 1.0) * max_turn,
+    };
+    Vec2::from_angle(turn).rotate(direction)
+}
+
+/// Head position after travelling for `delta` seconds. The arena edge is a wall
+/// the head slides along rather than a hazard.
+pub fn advance_head(rules: &Rules, head: Vec2, direction: Vec2, speed: f32, delta: f32) -> Vec2 {
+    let limit = rules.arena_half_size() - Vec2::splat(rules.snake_radius);
+    (head + direction * speed * delta).clamp(-limit, limit)
+}
+
+fn move_snake(snake: &mut SimSnake, steer: Steer, rules: &Rules, delta: f32) {
+    let head = snake.head();
+    snake.direction = steer_heading(
+        &rules.turning,
+        head,
+        snake.direction,
+        snake.segments.len(),
+        steer,
+        delta,
+    );
+    snake.age += delta;
+
+    let head = advance_head(rules, head, snake.direction, snake.speed, delta);
+    snake.segments[0].position = head;
+    snake.segments[0].heading = snake.direction;
+
+    snake.path.record(head);
+    // Keep some slack so freshly added segments have a path to settle onto.
+    snake
+        .path
+        .trim((snake.segments.len() + 4) as f32 * rules.segment_spacing);
+    for (index, segment) in snake.segments.iter_mut().enumerate().skip(1) {
+        if let Some((position, heading)) = snake.path.sample(index as f32 * rules.segment_spacing) {
+            segment.position = position;
+            if heading != Vec2::ZERO {
+                segment.heading = heading;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every snake chases its nearest coin for `seconds`.
+    fn play(seed: u64, seconds: f32) -> Simulation {
+        let mut simulation = Simulation::new(Rules::default(), seed, &Simulation::ring_spawns(3));
+        let delta = 1.0 / 60.0;
+        while simulation.time < seconds && simulation.alive() > 0 {
+            let steering: Vec<Steer> = (0..simulation.snakes.len())
+                .map(|index| {
+                    simulation
+                        .view(index)
+                        .nearest_coin()
+                        .map_or(Steer::Hold, Steer::Toward)
+                })
+                .collect();
+            simulation.step(delta, &steering);
+        }
+        simulation
+    }
+
+    fn outcome(simulation: &Simulation) -> Vec<(Vec2, u32, Option<DeathCause>, usize)> {
+        simulation
+            .snakes
+            .iter()
+            .map(|snake| (snake.head(), snake.score, snake.death, snake.segments.len()))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_plays_the_same_round() {
+        let (first, second) = (play(7, 30.0), play(7, 30.0));
+        assert_eq!(first.coins, second.coins);
+        assert_eq!(outcome(&first), outcome(&second));
+        assert!(first.snakes.iter().any(|snake| snake.score > 0));
+
+        assert_ne!(play(8, 30.0).coins, first.coins);
+    }
+
+    #[test]
+    fn other_snakes_count_before_your_own_body() {
+        let rules = Rules::default();
+        let head = Vec2::ZERO;
+        let touching = Vec2::new(rules.snake_radius, 0.0);
+        let other = [(2, vec![touching])];
+        assert_eq!(
+            head_collision(&rules, head, [(touching, true)], other.clone()),
+            Some(DeathCause::Snake(2))
+        );
+        assert_eq!(
+            head_collision(
+                &rules,
+                head,
+                [(Vec2::ZERO, true)],
+                Vec::<(usize, Vec<Vec2>)>::new()
+            ),
+            Some(DeathCause::OwnBody)
+        );
+    }
+
+    #[test]
+    fn grazes_and_new_segments_are_safe() {
+        let rules = Rules::default();
+        let head = Vec2::ZERO;
+        let graze = Vec2::new(rules.snake_radius * 2.0 - OTHER_OVERLAP + 0.5, 0.0);
+        assert_eq!(
+            head_collision(&rules, head, [(Vec2::ZERO, false)], [(1, [graze])]),
+            None
+        );
+    }
+
+    #[test]
+    fn turns_are_limited_by_the_turn_rate() {
+        let turning = Turning::default();
+        let behind = Steer::Toward(Vec2::new(-100.0, 1.0));
+        let delta = 1.0 / 60.0;
+        let heading = steer_heading(&turning, Vec2::ZERO, Vec2::X, 1, behind, delta);
+        let turned = Vec2::X.angle_between(heading);
+        assert!(
+            (turned - turning.turn_rate(1) * delta).abs() < 1e-4,
+            "{turned}"
+        );
+
+        // Targets inside the dead zone are ignored, and a still snake faces its
+        // first target at once.
+        let near = Steer::Toward(Vec2::new(0.0, turning.dead_zone * 0.5));
+        assert_eq!(
+            steer_heading(&turning, Vec2::ZERO, Vec2::X, 1, near, delta),
+            Vec2::X
+        );
+        let first = steer_heading(&turning, Vec2::ZERO, Vec2::ZERO, 1, behind, delta);
+        assert!(first.x < -0.99, "{first}");
+    }
+}
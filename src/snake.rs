@@ -1,138 +1,458 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
 use bevy::{
     prelude::*,
+    render::camera::NormalizedRenderTarget,
     sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    window::PrimaryWindow,
+};
+use bevy_snake::sim::{
+    self, advance_head, head_collision, steer_heading, BodyPath, Rules, Steer, Turning,
 };
 
 use crate::{
-    event::{GameOverEvent, SnakeCollideEvent},
-    game_state::{GameState, Score},
+    boost::{Boost, BoostConfig},
+    bot::{Bot, BrainKind, ExternalBot},
+    combo::{Combo, ComboConfig},
+    event::{DeathCause, GameOverEvent, NewRoundEvent, SnakeCollideEvent, SnakeDiedEvent},
+    game_state::{GameMode, GameState, Score},
+    power_up::{ActiveEffects, PowerUpConfig, PowerUpKind},
+    settings::Settings,
+    theme::ThemeMaterials,
 };
+
 pub struct SnakePlugin;
 
 impl Plugin for SnakePlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(MousePosition { x: 0.0, y: 0.0 })
-            .insert_resource(SnakeSegments::default())
-            .insert_resource(LastDirection(Vec2::ZERO))
-            .insert_resource(SnakeSpeed(200.0))
-            .add_systems(Startup, spawn_snake)
+        app.insert_resource(MousePosition(None))
+            .init_resource::<GameRules>()
+            .init_resource::<SegmentMesh>()
+            .add_systems(Startup, start_first_round)
             .add_systems(
                 Update,
                 (
-                    track_mouse_movements,
-                    snake_head_movement,
+                    start_round,
+                    (track_mouse_movements, apply_mouse_input).chain(),
+                    (snake_head_movement, record_snake_path, move_snake_segments).chain(),
                     add_snake_segment,
-                    move_snake_segments,
-                    check_snake_self_collision,
+                    check_snake_collisions,
                     update_segment_collision_flag,
                 ),
-            );
+            )
+            .add_systems(PostUpdate, resolve_deaths);
     }
 }
 
-#[derive(Resource, Default)]
-pub struct SnakeSegments(pub Vec<Entity>);
+/// The rules the local game plays by, shared with the headless simulation so
+/// both move, grow and crash snakes the same way.
+#[derive(Resource, Deref, Default)]
+pub struct GameRules(pub Rules);
 
+/// Cursor position in world space, or `None` while it is outside every game window.
 #[derive(Resource)]
-struct MousePosition {
-    x: f32,
-    y: f32,
+struct MousePosition(Option<Vec2>);
+
+#[derive(Resource)]
+struct SegmentMesh(Handle<Mesh>);
+
+impl FromWorld for SegmentMesh {
+    fn from_world(world: &mut World) -> Self {
+        let radius = world.resource::<GameRules>().snake_radius;
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        Self(meshes.add(Circle::new(radius)))
+    }
 }
 
 #[derive(Component)]
 pub struct SnakeHead;
 
+/// Who a snake belongs to, which also picks its colors. The first two are the
+/// local players; the rest are computer opponents.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Player(pub u8);
+
+impl Player {
+    pub const FIRST_BOT: u8 = 2;
+
+    pub fn is_bot(&self) -> bool {
+        self.0 >= Self::FIRST_BOT
+    }
+
+    pub fn label(&self) -> String {
+        if self.is_bot() {
+            format!("Bot {}", self.0 - Self::FIRST_BOT + 1)
+        } else {
+            format!("P{}", self.0 + 1)
+        }
+    }
+}
+
+/// Keys one player steers with when sharing the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyLayout {
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub boost: KeyCode,
+}
+
+impl KeyLayout {
+    pub const WASD: KeyLayout = KeyLayout {
+        left: KeyCode::KeyA,
+        right: KeyCode::KeyD,
+        boost: KeyCode::KeyW,
+    };
+    pub const ARROWS: KeyLayout = KeyLayout {
+        left: KeyCode::ArrowLeft,
+        right: KeyCode::ArrowRight,
+        boost: KeyCode::ArrowUp,
+    };
+}
+
+/// Where a snake takes its steering from each frame.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputSource {
+    /// Steers toward the cursor; boosts with the left button or `Space`.
+    Mouse,
+    /// Turns left and right at the full turn rate while the keys are held.
+    Keyboard(KeyLayout),
+    /// Steers toward whatever point its [`Bot`] brain picks, like a cursor.
+    Bot(BrainKind),
+}
+
+/// Point a cursor-style snake is steering toward, or `None` to hold its heading.
+#[derive(Component, Default)]
+pub struct SteeringTarget(pub Option<Vec2>);
+
+/// Turning limits for one snake's head. Starts from the [`GameRules`] turning
+/// and can be tuned per snake.
+#[derive(Component, Deref, DerefMut)]
+pub struct Steering(pub Turning);
+
+/// Every segment of one snake, head first. Lives on the head entity.
+#[derive(Component, Default)]
+pub struct SnakeSegments(pub Vec<Entity>);
+
+#[derive(Component)]
+pub struct SnakeSpeed(pub f32);
+
+/// Direction the head is travelling in, or zero before it has started moving.
+#[derive(Component)]
+pub struct LastDirection(pub Vec2);
+
+/// Where and how a snake enters the round.
+#[derive(Debug, Clone, Copy)]
+pub struct SnakeSpawn {
+    pub player: Player,
+    pub input: InputSource,
+    pub position: Vec2,
+    /// Initial heading, or zero to wait for the first steering input.
+    pub direction: Vec2,
+}
+
+impl SnakeSpawn {
+    /// Snakes that start a round with the current settings.
+    pub fn for_round(settings: &Settings) -> Vec<SnakeSpawn> {
+        let mut spawns = Self::players(settings.game_mode);
+        if settings.autopilot {
+            spawns[0].input = InputSource::Bot(settings.bot_brain);
+        }
+        // Bots start on a ring around the centre, facing inward.
+        let bot_count = settings.bot_count as usize;
+        for index in 0..bot_count {
+            let angle = FRAC_PI_2 + TAU * index as f32 / bot_count as f32;
+            let position = Vec2::from_angle(angle) * 500.0;
+            spawns.push(SnakeSpawn {
+                player: Player(Player::FIRST_BOT + index as u8),
+                input: InputSource::Bot(settings.bot_brain),
+                position,
+                direction: -position.normalize(),
+            });
+        }
+        spawns
+    }
+
+    fn players(mode: GameMode) -> Vec<SnakeSpawn> {
+        match mode {
+            GameMode::Solo => vec![SnakeSpawn {
+                player: Player(0),
+                input: InputSource::Mouse,
+                position: Vec2::ZERO,
+                direction: Vec2::ZERO,
+            }],
+            GameMode::Versus => vec![
+                SnakeSpawn {
+                    player: Player(0),
+                    input: InputSource::Keyboard(KeyLayout::WASD),
+                    position: Vec2::new(-400.0, 0.0),
+                    direction: Vec2::X,
+                },
+                SnakeSpawn {
+                    player: Player(1),
+                    input: InputSource::Keyboard(KeyLayout::ARROWS),
+                    position: Vec2::new(400.0, 0.0),
+                    direction: Vec2::NEG_X,
+                },
+            ],
+        }
+    }
+}
+
+/// Everything one snake owns. The snake entity is its head: it holds the body list,
+/// speed, direction and input source, so any number of snakes can share the arena
+/// whatever is steering them.
+#[derive(Bundle)]
+pub struct SnakeBundle {
+    head: SnakeHead,
+    player: Player,
+    input: InputSource,
+    steering: Steering,
+    segment: SnakeSegment,
+    segments: SnakeSegments,
+    speed: SnakeSpeed,
+    direction: LastDirection,
+    target: SteeringTarget,
+    path: SnakePath,
+    boost: Boost,
+    effects: ActiveEffects,
+    combo: Combo,
+    score: Score,
+    name: Name,
+}
+
+impl SnakeBundle {
+    pub fn new(
+        spawn: &SnakeSpawn,
+        rules: &Rules,
+        boost_config: &BoostConfig,
+        combo_config: &ComboConfig,
+    ) -> Self {
+        Self {
+            head: SnakeHead,
+            player: spawn.player,
+            input: spawn.input,
+            steering: Steering(rules.turning.clone()),
+            segment: SnakeSegment::default(),
+            // Filled in with the head's own entity once it is spawned.
+            segments: SnakeSegments::default(),
+            speed: SnakeSpeed(rules.start_speed),
+            direction: LastDirection(spawn.direction),
+            target: SteeringTarget::default(),
+            path: SnakePath::default(),
+            boost: Boost::new(boost_config),
+            effects: ActiveEffects::default(),
+            combo: Combo::new(combo_config),
+            score: Score(0),
+            name: Name::new(format!("Snake {}", spawn.player.label())),
+        }
+    }
+}
+
+/// Marks a snake that died this frame; it is despawned at the end of the frame.
+#[derive(Component)]
+struct Dead;
+
 #[derive(Component, Default)]
 pub struct SnakeSegment {
     ignore_collision: bool,
     collision_timer: Timer,
 }
 
-#[derive(Resource)]
-pub struct SnakeSpeed(pub f32);
+impl SnakeSegment {
+    /// Whether the head can currently crash into this segment.
+    pub fn collides(&self) -> bool {
+        !self.ignore_collision
+    }
+}
 
-#[derive(Resource)]
-struct LastDirection(Vec2);
+/// Recent head positions the body follows. Lives on the head entity.
+#[derive(Component, Default)]
+pub struct SnakePath(BodyPath);
 
-fn spawn_snake(
+fn start_first_round(mut new_round_event: EventWriter<NewRoundEvent>) {
+    new_round_event.send(NewRoundEvent);
+}
+
+/// Replaces every snake with a fresh one per player in the current game mode.
+fn start_round(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut snake_segments: ResMut<SnakeSegments>,
+    mut new_round_event: EventReader<NewRoundEvent>,
+    snake_query: Query<(Entity, &SnakeSegments)>,
+    segment_mesh: Res<SegmentMesh>,
+    theme_materials: Res<ThemeMaterials>,
+    boost_config: Res<BoostConfig>,
+    combo_config: Res<ComboConfig>,
+    rules: Res<GameRules>,
+    settings: Res<Settings>,
+    external_bot: Res<ExternalBot>,
 ) {
-    let snake = commands
-        .spawn(MaterialMesh2dBundle {
-            mesh: Mesh2dHandle(meshes.add(Circle::new(10.0))),
-            material: materials.add(Color::GREEN),
-            transform: Transform::from_xyz(0.0, 0.0, 0.0),
-            ..default()
-        })
-        .insert(SnakeHead)
-        .insert(SnakeSegment::default())
-        .insert(Name::new("SnakeHead"))
-        .id();
+    if new_round_event.read().count() == 0 {
+        return;
+    }
+    for (_, segments) in snake_query.iter() {
+        for segment in segments.0.iter() {
+            commands.entity(*segment).despawn();
+        }
+    }
 
-    snake_segments.0.push(snake);
+    for spawn in SnakeSpawn::for_round(&settings) {
+        let rotation = if spawn.direction == Vec2::ZERO {
+            Quat::IDENTITY
+        } else {
+            Quat::from_rotation_z(Vec2::X.angle_between(spawn.direction))
+        };
+        let mut head = commands.spawn((
+            SnakeBundle::new(&spawn, &rules, &boost_config, &combo_config),
+            MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(segment_mesh.0.clone()),
+                material: theme_materials.snake(spawn.player).head.clone(),
+                transform: Transform::from_translation(spawn.position.extend(0.0))
+                    .with_rotation(rotation),
+                ..default()
+            },
+        ));
+        let head_id = head.id();
+        head.insert(SnakeSegments(vec![head_id]));
+        if let InputSource::Bot(brain) = spawn.input {
+            let brain = external_bot
+                .brain(spawn.player, brain)
+                .unwrap_or_else(|| brain.brain());
+            head.insert(Bot(brain));
+        }
+    }
 }
 
+/// Converts the cursor to world space every frame, since the camera moves even
+/// when the mouse doesn't. Uses whichever camera renders to the window the
+/// cursor is over; the cursor is relative to that window, so the letterboxed
+/// viewport's offset is removed first.
 fn track_mouse_movements(
-    mut mouse_motion_events: EventReader<CursorMoved>,
     mut mouse_position: ResMut<MousePosition>,
-    mut windows: Query<&mut Window>,
+    windows: Query<&Window>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
 ) {
-    let window = windows.single_mut();
-    for event in mouse_motion_events.read() {
-        let position = event.position - Vec2::new(window.width() / 2.0, window.height() / 2.0);
-        mouse_position.x = position.x;
-        mouse_position.y = -position.y;
+    let primary_window = primary_window.get_single().ok();
+    mouse_position.0 = camera_query
+        .iter()
+        .filter(|(camera, _)| camera.is_active)
+        .find_map(|(camera, camera_transform)| {
+            let NormalizedRenderTarget::Window(window) = camera.target.normalize(primary_window)?
+            else {
+                return None;
+            };
+            let cursor = windows.get(window.entity()).ok()?.cursor_position()?;
+            let viewport = camera.logical_viewport_rect()?;
+            camera.viewport_to_world_2d(camera_transform, cursor - viewport.min)
+        });
+}
+
+/// Mouse-controlled snakes steer toward the cursor through the same target bots use.
+fn apply_mouse_input(
+    mouse_position: Res<MousePosition>,
+    mut snake_query: Query<(&InputSource, &mut SteeringTarget)>,
+) {
+    for (input, mut target) in snake_query.iter_mut() {
+        if input == &InputSource::Mouse {
+            // Without a cursor the snake holds its heading instead of chasing a stale point.
+            target.0 = mouse_position.0;
+        }
     }
 }
 
 fn snake_head_movement(
-    snake_speed: ResMut<SnakeSpeed>,
     time: Res<Time>,
-    mouse_position: Res<MousePosition>,
-    mut snake_last_direction: ResMut<LastDirection>,
-    mut query: Query<(&mut Transform, Entity), With<SnakeHead>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut query: Query<
+        (
+            &mut Transform,
+            &mut LastDirection,
+            &Steering,
+            &InputSource,
+            &SteeringTarget,
+            &SnakeSegments,
+            &SnakeSpeed,
+            &ActiveEffects,
+            &Boost,
+        ),
+        With<SnakeHead>,
+    >,
+    power_up_config: Res<PowerUpConfig>,
+    boost_config: Res<BoostConfig>,
+    rules: Res<GameRules>,
     state: Res<State<GameState>>,
 ) {
     if state.get() != &GameState::InGame {
         return;
     }
-    for (mut transform, _entity) in query.iter_mut() {
-        let snake_pos = Vec2::new(transform.translation.x, transform.translation.y);
-        let mouse_pos = Vec2::new(mouse_position.x, mouse_position.y);
-        if (mouse_pos - snake_pos).length() > 1.0 {
-            snake_last_direction.0 = (mouse_pos - snake_pos).normalize_or_zero();
-        }
+    let delta = time.delta_seconds();
+    for (
+        mut transform,
+        mut last_direction,
+        steering,
+        input,
+        steering_target,
+        segments,
+        snake_speed,
+        effects,
+        boost,
+    ) in query.iter_mut()
+    {
+        let steer = match input {
+            InputSource::Mouse | InputSource::Bot(_) => {
+                steering_target.0.map_or(Steer::Hold, Steer::Toward)
+            }
+            InputSource::Keyboard(layout) => {
+                let mut share = 0.0;
+                if keyboard_input.pressed(layout.left) {
+                    share += 1.0;
+                }
+                if keyboard_input.pressed(layout.right) {
+                    share -= 1.0;
+                }
+                Steer::Turn(share)
+            }
+        };
+        let head = transform.translation.truncate();
+        last_direction.0 = steer_heading(
+            steering,
+            head,
+            last_direction.0,
+            segments.0.len(),
+            steer,
+            delta,
+        );
 
-        let angle_to_mouse = Vec2::X.angle_between(snake_last_direction.0);
+        let speed = snake_speed.0
+            * effects.speed_scale(&power_up_config)
+            * boost.speed_scale(&boost_config);
 
-        transform.rotation = Quat::from_rotation_z(angle_to_mouse);
-        transform.translation +=
-            (snake_last_direction.0 * snake_speed.0 * time.delta_seconds()).extend(0.0);
+        transform.rotation = Quat::from_rotation_z(Vec2::X.angle_between(last_direction.0));
+        let position = advance_head(&rules, head, last_direction.0, speed, delta);
+        transform.translation = position.extend(transform.translation.z);
     }
 }
 
 fn add_snake_segment(
     mut commands: Commands,
     mut snake_collide_event: EventReader<SnakeCollideEvent>,
-    mut snake_segments: ResMut<SnakeSegments>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut snake_speed: ResMut<SnakeSpeed>,
+    mut snake_query: Query<(&mut SnakeSegments, &mut SnakeSpeed, &Player)>,
+    theme_materials: Res<ThemeMaterials>,
+    segment_mesh: Res<SegmentMesh>,
     segment_query: Query<&Transform, With<SnakeSegment>>,
-    mut score: ResMut<Score>,
+    rules: Res<GameRules>,
     state: Res<State<GameState>>,
 ) {
     if state.get() != &GameState::InGame {
         return;
     }
-    let snake_segment_mesh = meshes.add(Circle::new(10.0));
-    let snake_segment_material = materials.add(Color::GREEN);
 
-    for _ in snake_collide_event.read() {
+    for event in snake_collide_event.read() {
         info!("Snake collided event received");
+        let Ok((mut snake_segments, mut snake_speed, player)) = snake_query.get_mut(event.snake)
+        else {
+            continue;
+        };
 
         if let Some(tail_segment) = snake_segments.0.last() {
             if let Ok(segment_transform) = segment_query.get(*tail_segment) {
@@ -141,85 +461,157 @@ fn add_snake_segment(
 
                 let snake_segment = commands
                     .spawn(MaterialMesh2dBundle {
-                        mesh: Mesh2dHandle(snake_segment_mesh.clone()),
-                        material: snake_segment_material.clone(),
+                        mesh: Mesh2dHandle(segment_mesh.0.clone()),
+                        material: theme_materials.snake(*player).body.clone(),
                         transform,
                         ..default()
                     })
                     .insert(SnakeSegment {
+                        // New segments can be passed through until they settle in.
                         ignore_collision: true,
-                        collision_timer: Timer::from_seconds(2.0, TimerMode::Repeating), // Newly spawned segments will start with collision ignored
+                        collision_timer: Timer::from_seconds(
+                            rules.new_segment_grace,
+                            TimerMode::Repeating,
+                        ),
                     })
                     .insert(Name::new("SnakeSegment"))
                     .id();
 
                 snake_segments.0.push(snake_segment);
-                snake_speed.0 += 10.0;
-                score.0 += 1;
+                if event.speed_up {
+                    snake_speed.0 += rules.speed_per_segment;
+                }
             }
         }
     }
 }
 
-fn move_snake_segments(
-    segments: Res<SnakeSegments>,
-    mut transforms: Query<&mut Transform>,
-    time: Res<Time>,
-    snake_speed: ResMut<SnakeSpeed>,
+fn record_snake_path(
+    mut snake_query: Query<(&mut SnakePath, &SnakeSegments, &Transform), With<SnakeHead>>,
+    rules: Res<GameRules>,
     state: Res<State<GameState>>,
 ) {
     if state.get() != &GameState::InGame {
         return;
     }
-    let mut previous_position = Vec3::ZERO;
-    let mut previous_rotation = Quat::IDENTITY;
-
-    if let Ok(head_transform) = transforms.get(segments.0[0]) {
-        previous_position = head_transform.translation;
-        previous_rotation = head_transform.rotation;
+    for (mut snake_path, segments, head_transform) in snake_query.iter_mut() {
+        snake_path.0.record(head_transform.translation.truncate());
+        // Keep some slack so freshly added segments have a path to settle onto.
+        snake_path
+            .0
+            .trim((segments.0.len() + 4) as f32 * rules.segment_spacing);
     }
-    let base_lerp_factor = 10.0 * time.delta_seconds();
-    let lerp_factor = base_lerp_factor + (snake_speed.0 * 0.0003);
-
-    for segment in segments.0.iter().skip(1) {
-        if let Ok(mut transform) = transforms.get_mut(*segment) {
-            let current_position = transform.translation;
-            let current_rotation = transform.rotation;
-
-            transform.translation = transform.translation.lerp(previous_position, lerp_factor);
-            transform.rotation = transform.rotation.slerp(previous_rotation, lerp_factor);
+}
 
-            previous_position = current_position;
-            previous_rotation = current_rotation;
+fn move_snake_segments(
+    snake_query: Query<(&SnakeSegments, &SnakePath)>,
+    mut transforms: Query<&mut Transform, Without<SnakeHead>>,
+    rules: Res<GameRules>,
+    state: Res<State<GameState>>,
+) {
+    if state.get() != &GameState::InGame {
+        return;
+    }
+    for (segments, snake_path) in snake_query.iter() {
+        for (index, segment) in segments.0.iter().enumerate().skip(1) {
+            let Some((position, heading)) =
+                snake_path.0.sample(index as f32 * rules.segment_spacing)
+            else {
+                continue;
+            };
+            if let Ok(mut transform) = transforms.get_mut(*segment) {
+                transform.translation = position.extend(transform.translation.z);
+                if heading != Vec2::ZERO {
+                    transform.rotation = Quat::from_rotation_z(Vec2::X.angle_between(heading));
+                }
+            }
         }
     }
 }
 
-fn check_snake_self_collision(
-    _commands: Commands,
-    head_query: Query<&Transform, With<SnakeHead>>,
-    segment_query: Query<(Entity, &Transform, &SnakeSegment), Without<SnakeHead>>,
-    mut game_over_event: EventWriter<GameOverEvent>,
-    _next_state: ResMut<NextState<GameState>>,
+/// Kills heads that run into their own body or into any part of another snake.
+/// Each snake is checked against its own body and every other body separately,
+/// and the snake that was hit gets the kill.
+fn check_snake_collisions(
+    mut commands: Commands,
+    snake_query: Query<(Entity, &Player, &SnakeSegments, &ActiveEffects), With<SnakeHead>>,
+    segment_query: Query<(&Transform, &SnakeSegment)>,
+    mut snake_died_event: EventWriter<SnakeDiedEvent>,
+    rules: Res<GameRules>,
+    state: Res<State<GameState>>,
 ) {
-    if let Ok(head_transform) = head_query.get_single() {
-        let head_position = head_transform.translation.truncate();
-        let snake_radius = 10.0;
+    if state.get() != &GameState::InGame {
+        return;
+    }
+    // Segment positions per snake, head first, with whether each one can be hit.
+    let bodies: Vec<(Entity, Player, Vec<(Vec2, bool)>)> = snake_query
+        .iter()
+        .map(|(snake, player, segments, _)| {
+            let body = segments
+                .0
+                .iter()
+                .filter_map(|segment| segment_query.get(*segment).ok())
+                .map(|(transform, segment)| (transform.translation.truncate(), segment.collides()))
+                .collect();
+            (snake, *player, body)
+        })
+        .collect();
 
-        for (_segment_entity, segment_transform, segment) in segment_query.iter() {
-            if segment.ignore_collision {
-                continue;
-            }
+    for (snake, player, body) in bodies.iter() {
+        let Ok((.., effects)) = snake_query.get(*snake) else {
+            continue;
+        };
+        if effects.is_active(PowerUpKind::Ghost) {
+            continue;
+        }
+        let Some(&(head_position, _)) = body.first() else {
+            continue;
+        };
 
-            let segment_position = segment_transform.translation.truncate();
-            let distance = head_position.distance(segment_position);
+        let others = bodies
+            .iter()
+            .enumerate()
+            .filter(|(_, (other, ..))| other != snake)
+            .map(|(index, (.., other_body))| {
+                (index, other_body.iter().map(|(position, _)| *position))
+            });
+        let cause =
+            match head_collision(&rules, head_position, body.iter().skip(1).copied(), others) {
+                Some(sim::DeathCause::Snake(index)) => DeathCause::Snake(bodies[index].1),
+                Some(sim::DeathCause::OwnBody) => DeathCause::OwnBody,
+                None => continue,
+            };
+        info!("{} died: {:?}", player.label(), cause);
+        commands.entity(*snake).insert(Dead);
+        snake_died_event.send(SnakeDiedEvent {
+            player: *player,
+            cause,
+            body: body.iter().map(|(position, _)| *position).collect(),
+        });
+    }
+}
 
-            if distance + 7.0 < snake_radius {
-                println!("Collision with snake segment detected! Game Over.");
-                game_over_event.send(GameOverEvent);
-            }
+/// Removes snakes that died this frame, and ends the game or round once too few are left.
+fn resolve_deaths(
+    mut commands: Commands,
+    dead_query: Query<&SnakeSegments, With<Dead>>,
+    alive_query: Query<&Player, (With<SnakeHead>, Without<Dead>)>,
+    mut game_over_event: EventWriter<GameOverEvent>,
+    settings: Res<Settings>,
+) {
+    if dead_query.is_empty() {
+        return;
+    }
+    for segments in dead_query.iter() {
+        for segment in segments.0.iter() {
+            commands.entity(*segment).despawn();
         }
     }
+    // Bots dying never ends the round; only the players' snakes count.
+    let players_alive = alive_query.iter().filter(|player| !player.is_bot()).count();
+    if settings.game_mode.round_over(players_alive) {
+        game_over_event.send(GameOverEvent);
+    }
 }
 
 fn update_segment_collision_flag(mut segment_query: Query<&mut SnakeSegment>, time: Res<Time>) {
@@ -0,0 +1,266 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    prelude::*,
+    render::{mesh::PrimitiveTopology, render_asset::RenderAssetUsages, view::NoFrustumCulling},
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use crate::{
+    snake::{Player, SnakeHead, SnakeSegment, SnakeSegments},
+    theme::ThemeMaterials,
+};
+
+pub struct SnakeRenderPlugin;
+
+impl Plugin for SnakeRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SnakeRenderSettings::default())
+            .add_systems(Update, toggle_render_mode)
+            .add_systems(
+                PostUpdate,
+                (sync_body_meshes, apply_render_mode, update_body_mesh).chain(),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnakeRenderMode {
+    /// One circle mesh per segment.
+    Circles,
+    /// A single triangle strip stretched along all segments, with the head drawn on top.
+    Mesh,
+}
+
+#[derive(Resource)]
+pub struct SnakeRenderSettings {
+    pub mode: SnakeRenderMode,
+    pub head_width: f32,
+    pub tail_width: f32,
+    /// World units covered by one repeat of the skin texture along the body.
+    pub texture_length: f32,
+    /// Number of strip rows used to round off the head and the tail.
+    pub cap_rows: usize,
+}
+
+impl Default for SnakeRenderSettings {
+    fn default() -> Self {
+        Self {
+            mode: SnakeRenderMode::Mesh,
+            head_width: 20.0,
+            tail_width: 8.0,
+            texture_length: 32.0,
+            cap_rows: 6,
+        }
+    }
+}
+
+/// Body strip drawn for the snake whose head is `snake`.
+#[derive(Component)]
+struct SnakeBodyMesh {
+    snake: Entity,
+}
+
+/// One cross-section of the strip: two vertices either side of `center`.
+struct StripRow {
+    center: Vec2,
+    normal: Vec2,
+    half_width: f32,
+}
+
+/// Gives every new snake a body mesh and removes the meshes of snakes that are gone.
+fn sync_body_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    theme_materials: Res<ThemeMaterials>,
+    new_head_query: Query<(Entity, &Player), Added<SnakeHead>>,
+    head_query: Query<(), With<SnakeHead>>,
+    body_mesh_query: Query<(Entity, &SnakeBodyMesh)>,
+) {
+    for (entity, body_mesh) in body_mesh_query.iter() {
+        if !head_query.contains(body_mesh.snake) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for (snake, player) in new_head_query.iter() {
+        let mesh = Mesh::new(
+            PrimitiveTopology::TriangleStrip,
+            RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, Vec::<[f32; 3]>::new())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, Vec::<[f32; 3]>::new())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, Vec::<[f32; 2]>::new());
+
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(mesh)),
+                material: theme_materials.snake(*player).body_mesh.clone(),
+                // Behind the head circle, which stays visible to show the head sprite.
+                transform: Transform::from_xyz(0.0, 0.0, -0.5),
+                ..default()
+            },
+            // The vertices move every frame, so the bounds computed at spawn are never valid.
+            NoFrustumCulling,
+            SnakeBodyMesh { snake },
+            Name::new("SnakeBodyMesh"),
+        ));
+    }
+}
+
+fn toggle_render_mode(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<SnakeRenderSettings>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F2) {
+        settings.mode = match settings.mode {
+            SnakeRenderMode::Circles => SnakeRenderMode::Mesh,
+            SnakeRenderMode::Mesh => SnakeRenderMode::Circles,
+        };
+    }
+}
+
+fn apply_render_mode(
+    settings: Res<SnakeRenderSettings>,
+    mut segment_query: Query<
+        &mut Visibility,
+        (
+            With<SnakeSegment>,
+            Without<SnakeHead>,
+            Without<SnakeBodyMesh>,
+        ),
+    >,
+    mut body_mesh_query: Query<&mut Visibility, With<SnakeBodyMesh>>,
+) {
+    let (segment_visibility, mesh_visibility) = match settings.mode {
+        SnakeRenderMode::Circles => (Visibility::Inherited, Visibility::Hidden),
+        SnakeRenderMode::Mesh => (Visibility::Hidden, Visibility::Inherited),
+    };
+    for mut visibility in segment_query.iter_mut() {
+        *visibility = segment_visibility;
+    }
+    for mut visibility in body_mesh_query.iter_mut() {
+        *visibility = mesh_visibility;
+    }
+}
+
+fn update_body_mesh(
+    settings: Res<SnakeRenderSettings>,
+    transforms: Query<&Transform, With<SnakeSegment>>,
+    head_query: Query<(&Transform, &SnakeSegments), With<SnakeHead>>,
+    body_mesh_query: Query<(&Mesh2dHandle, &SnakeBodyMesh)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    if settings.mode != SnakeRenderMode::Mesh {
+        return;
+    }
+    for (Mesh2dHandle(handle), body_mesh) in body_mesh_query.iter() {
+        let Ok((head_transform, segments)) = head_query.get(body_mesh.snake) else {
+            continue;
+        };
+        let Some(mesh) = meshes.get_mut(handle) else {
+            continue;
+        };
+
+        let points: Vec<Vec2> = segments
+            .0
+            .iter()
+            .filter_map(|segment| transforms.get(*segment).ok())
+            .map(|transform| transform.translation.truncate())
+            .collect();
+        let head_heading = (head_transform.rotation * Vec3::X).truncate();
+        write_strip(
+            mesh,
+            &build_strip_rows(&points, head_heading, &settings),
+            &settings,
+        );
+    }
+}
+
+fn write_strip(mesh: &mut Mesh, rows: &[StripRow], settings: &SnakeRenderSettings) {
+    let mut positions = Vec::with_capacity(rows.len() * 2);
+    let mut uvs = Vec::with_capacity(rows.len() * 2);
+    let mut distance = 0.0;
+    let mut previous_center = rows.first().map(|row| row.center).unwrap_or_default();
+    for row in rows.iter() {
+        distance += previous_center.distance(row.center);
+        previous_center = row.center;
+        let u = distance / settings.texture_length;
+        let left = row.center + row.normal * row.half_width;
+        let right = row.center - row.normal * row.half_width;
+        positions.push([left.x, left.y, 0.0]);
+        positions.push([right.x, right.y, 0.0]);
+        uvs.push([u, 0.0]);
+        uvs.push([u, 1.0]);
+    }
+    let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+}
+
+/// Lays out strip rows from the tail tip to the head tip. `points` run head first.
+fn build_strip_rows(
+    points: &[Vec2],
+    head_heading: Vec2,
+    settings: &SnakeRenderSettings,
+) -> Vec<StripRow> {
+    let Some(&head) = points.first() else {
+        return Vec::new();
+    };
+    let count = points.len();
+    let half_width_at = |index: usize| {
+        let t = if count > 1 {
+            index as f32 / (count - 1) as f32
+        } else {
+            0.0
+        };
+        (settings.head_width + (settings.tail_width - settings.head_width) * t) / 2.0
+    };
+    // Direction each point faces, pointing toward the head.
+    let heading_at = |index: usize| {
+        if index == 0 {
+            return head_heading;
+        }
+        let ahead = points[index - 1];
+        let behind = points.get(index + 1).copied().unwrap_or(points[index]);
+        (ahead - behind).try_normalize().unwrap_or(head_heading)
+    };
+
+    let cap_rows = settings.cap_rows.max(1);
+    let mut rows = Vec::with_capacity(count + cap_rows * 2);
+
+    let tail_index = count - 1;
+    let tail = points[tail_index];
+    let tail_heading = heading_at(tail_index);
+    let tail_half_width = half_width_at(tail_index);
+    for step in (1..=cap_rows).rev() {
+        let angle = FRAC_PI_2 * step as f32 / cap_rows as f32;
+        rows.push(StripRow {
+            center: tail - tail_heading * tail_half_width * angle.sin(),
+            normal: tail_heading.perp(),
+            half_width: tail_half_width * angle.cos(),
+        });
+    }
+
+    for index in (0..count).rev() {
+        rows.push(StripRow {
+            center: points[index],
+            normal: heading_at(index).perp(),
+            half_width: half_width_at(index),
+        });
+    }
+
+    let head_half_width = half_width_at(0);
+    for step in 1..=cap_rows {
+        let angle = FRAC_PI_2 * step as f32 / cap_rows as f32;
+        rows.push(StripRow {
+            center: head + head_heading * head_half_width * angle.sin(),
+            normal: head_heading.perp(),
+            half_width: head_half_width * angle.cos(),
+        });
+    }
+
+    rows
+}
@@ -0,0 +1,305 @@
+use bevy::{prelude::*, ui::RelativeCursorPosition};
+use bevy_snake::net::Replay;
+
+use crate::online::{OnlineOptions, ViewFrame};
+
+/// Spectator controls for watching a server or a replay: following any snake
+/// with the camera, and pausing, seeking and scrubbing through replays.
+pub struct SpectatePlugin;
+
+impl Plugin for SpectatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Followed>()
+            .insert_resource(PlaybackConfig::default())
+            .add_systems(
+                Update,
+                (
+                    spawn_timeline.run_if(resource_added::<Playback>),
+                    (scrub_timeline, control_playback, update_timeline)
+                        .chain()
+                        .run_if(resource_exists::<Playback>),
+                    choose_followed.run_if(spectating),
+                )
+                    .chain()
+                    .in_set(SpectateSet),
+            );
+    }
+}
+
+/// Runs before the online view is drawn, so it shows this frame's choices.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpectateSet;
+
+#[derive(Resource)]
+pub struct PlaybackConfig {
+    /// Seconds skipped by the arrow keys.
+    pub seek_step: f64,
+    pub min_speed: f64,
+    pub max_speed: f64,
+}
+
+impl Default for PlaybackConfig {
+    fn default() -> Self {
+        Self {
+            seek_step: 5.0,
+            min_speed: 0.25,
+            max_speed: 8.0,
+        }
+    }
+}
+
+/// Id of the snake the camera follows, or `None` to show the whole arena.
+/// Players always follow their own snake instead.
+#[derive(Resource, Default)]
+pub struct Followed(pub Option<u8>);
+
+/// A replay being watched, and where in it we are.
+#[derive(Resource)]
+pub struct Playback {
+    replay: Replay,
+    round_starts: Vec<f64>,
+    /// Seconds from the start of the replay.
+    pub time: f64,
+    pub speed: f64,
+    pub paused: bool,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Playback {
+        Playback {
+            round_starts: replay.round_starts(),
+            replay,
+            time: 0.0,
+            speed: 1.0,
+            paused: false,
+        }
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.replay.duration()
+    }
+
+    fn seek(&mut self, time: f64) {
+        self.time = time.clamp(0.0, self.duration());
+    }
+
+    /// A line for the HUD with the position, speed and controls.
+    pub fn status(&self) -> String {
+        let clock = |seconds: f64| {
+            let seconds = seconds as u64;
+            format!("{}:{:02}", seconds / 60, seconds % 60)
+        };
+        let state = if self.paused { "  paused" } else { "" };
+        format!(
+            "Replay {} / {}  x{}{state}\n\
+             Space: pause   Left/Right: seek   Up/Down: speed   PgUp/PgDn: round   Home: restart",
+            clock(self.time),
+            clock(self.duration()),
+            self.speed,
+        )
+    }
+}
+
+#[derive(Component)]
+struct Timeline;
+
+#[derive(Component)]
+struct TimelineFill;
+
+fn spectating(options: Res<OnlineOptions>) -> bool {
+    options.source.is_spectating()
+}
+
+/// A bar along the bottom of the screen showing how far into the replay we
+/// are, with a tick at every round start. Click or drag on it to jump.
+fn spawn_timeline(mut commands: Commands, playback: Res<Playback>) {
+    let duration = playback.duration().max(f64::EPSILON);
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(24.0),
+                    left: Val::Percent(10.0),
+                    width: Val::Percent(80.0),
+                    height: Val::Px(14.0),
+                    ..default()
+                },
+                background_color: Color::rgba(1.0, 1.0, 1.0, 0.2).into(),
+                ..default()
+            },
+            Interaction::default(),
+            RelativeCursorPosition::default(),
+            Timeline,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.9, 0.9, 0.9).into(),
+                    ..default()
+                },
+                TimelineFill,
+            ));
+            for start in playback.round_starts.iter().skip(1) {
+                parent.spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Percent((start / duration * 100.0) as f32),
+                        width: Val::Px(2.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: Color::ORANGE_RED.into(),
+                    ..default()
+                });
+            }
+        });
+}
+
+fn scrub_timeline(
+    mut playback: ResMut<Playback>,
+    timeline_query: Query<(&Interaction, &RelativeCursorPosition), With<Timeline>>,
+) {
+    for (interaction, cursor) in timeline_query.iter() {
+        if let (Interaction::Pressed, Some(cursor)) = (interaction, cursor.normalized) {
+            let time = cursor.x.clamp(0.0, 1.0) as f64 * playback.duration();
+            playback.seek(time);
+        }
+    }
+}
+
+fn control_playback(
+    mut playback: ResMut<Playback>,
+    mut view_frame: ResMut<ViewFrame>,
+    config: Res<PlaybackConfig>,
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+) {
+    if keys.just_pressed(KeyCode::Space) {
+        // Playing from the end starts over.
+        if playback.paused && playback.time >= playback.duration() {
+            playback.time = 0.0;
+        }
+        playback.paused = !playback.paused;
+    }
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        let time = playback.time - config.seek_step;
+        playback.seek(time);
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        let time = playback.time + config.seek_step;
+        playback.seek(time);
+    }
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        playback.speed = (playback.speed * 2.0).min(config.max_speed);
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        playback.speed = (playback.speed / 2.0).max(config.min_speed);
+    }
+    if keys.just_pressed(KeyCode::Home) {
+        playback.seek(0.0);
+    }
+    if keys.just_pressed(KeyCode::PageUp) {
+        // A moment into a round still goes back to the one before.
+        let start = playback
+            .round_starts
+            .iter()
+            .rev()
+            .find(|start| **start < playback.time - 1.0)
+            .copied()
+            .unwrap_or(0.0);
+        playback.seek(start);
+    }
+    if keys.just_pressed(KeyCode::PageDown) {
+        let end = playback.duration();
+        let start = playback
+            .round_starts
+            .iter()
+            .find(|start| **start > playback.time)
+            .copied()
+            .unwrap_or(end);
+        playback.seek(start);
+    }
+
+    if !playback.paused {
+        let time = playback.time + time.delta_seconds_f64() * playback.speed;
+        playback.seek(time);
+        if playback.time >= playback.duration() {
+            playback.paused = true;
+        }
+    }
+    view_frame.0 = Some(playback.replay.frame(playback.time));
+}
+
+fn update_timeline(playback: Res<Playback>, mut fill_query: Query<&mut Style, With<TimelineFill>>) {
+    let progress = playback.time / playback.duration().max(f64::EPSILON);
+    for mut style in fill_query.iter_mut() {
+        style.width = Val::Percent((progress * 100.0) as f32);
+    }
+}
+
+/// Tab and Shift+Tab step through the live snakes, 1 to 9 pick one by seat and
+/// 0 shows the whole arena.
+fn choose_followed(
+    mut followed: ResMut<Followed>,
+    view_frame: Res<ViewFrame>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    let Some(frame) = &view_frame.0 else {
+        return;
+    };
+    let mut ids: Vec<u8> = frame.snakes.iter().map(|snake| snake.id).collect();
+    ids.sort_unstable();
+
+    if keys.just_pressed(KeyCode::Tab) {
+        let alive: Vec<u8> = ids
+            .iter()
+            .copied()
+            .filter(|id| {
+                frame
+                    .snakes
+                    .iter()
+                    .any(|snake| snake.id == *id && snake.alive)
+            })
+            .collect();
+        if !alive.is_empty() {
+            let current = followed
+                .0
+                .and_then(|id| alive.iter().position(|alive| *alive == id));
+            let backward = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+            let index = match (current, backward) {
+                (None, false) => 0,
+                (None, true) => alive.len() - 1,
+                (Some(index), false) => (index + 1) % alive.len(),
+                (Some(index), true) => (index + alive.len() - 1) % alive.len(),
+            };
+            followed.0 = Some(alive[index]);
+        }
+    }
+
+    let digits = [
+        KeyCode::Digit0,
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+    for (digit, key) in digits.into_iter().enumerate() {
+        if keys.just_pressed(key) {
+            followed.0 = match digit {
+                0 => None,
+                seat => ids.get(seat - 1).copied().or(followed.0),
+            };
+        }
+    }
+}
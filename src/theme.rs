@@ -0,0 +1,403 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadedFolder},
+    prelude::*,
+    render::texture::{
+        ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor,
+    },
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    accessibility::{apply_high_contrast, ColorPalette},
+    power_up::PowerUpKind,
+    settings::Settings,
+    snake::Player,
+};
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Theme>()
+            .init_asset_loader::<ThemeLoader>()
+            .init_resource::<ThemeMaterials>()
+            .add_systems(Startup, load_themes)
+            .add_systems(Update, (collect_themes, apply_theme).chain());
+    }
+}
+
+/// Palette and sprites for the snake, coins, background and UI, loaded from
+/// `assets/themes/*.theme.ron`.
+#[derive(Asset, TypePath, Debug)]
+pub struct Theme {
+    pub name: String,
+    pub palette: Palette,
+    pub head_sprite: Option<String>,
+    pub body_texture: Option<String>,
+    pub coin_sprite: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Palette {
+    pub snake_head: Color,
+    pub snake_body: Color,
+    /// Colors of the second player's snake in versus mode.
+    pub rival_head: Color,
+    pub rival_body: Color,
+    pub coin: Color,
+    pub pellet: Color,
+    /// One color per power-up, in [`PowerUpKind::ALL`] order.
+    pub power_ups: [Color; 4],
+    pub background: Color,
+    pub ui_text: Color,
+}
+
+/// On-disk form of a [`Theme`], with colors written as hex strings.
+#[derive(Deserialize)]
+struct ThemeFile {
+    name: String,
+    snake_head: String,
+    snake_body: String,
+    /// Falls back to the snake colors with their hue turned halfway round.
+    #[serde(default)]
+    rival_head: Option<String>,
+    #[serde(default)]
+    rival_body: Option<String>,
+    coin: String,
+    pellet: String,
+    power_ups: PowerUpColorsFile,
+    background: String,
+    ui_text: String,
+    head_sprite: Option<String>,
+    body_texture: Option<String>,
+    coin_sprite: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PowerUpColorsFile {
+    magnet: String,
+    ghost: String,
+    slow_motion: String,
+    score_doubler: String,
+}
+
+#[derive(Debug, Error)]
+pub enum ThemeLoaderError {
+    #[error("could not read theme file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse theme file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("invalid color `{0}` in theme file")]
+    Color(String),
+}
+
+#[derive(Default)]
+struct ThemeLoader;
+
+impl AssetLoader for ThemeLoader {
+    type Asset = Theme;
+    type Settings = ();
+    type Error = ThemeLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Theme, ThemeLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let file: ThemeFile = ron::de::from_bytes(&bytes)?;
+            let color = |hex: String| Color::hex(&hex).map_err(|_| ThemeLoaderError::Color(hex));
+            let snake_head = color(file.snake_head)?;
+            let snake_body = color(file.snake_body)?;
+            let power_ups = file.power_ups;
+            let rival = |hex: Option<String>, fallback: Color| {
+                hex.map_or(Ok(rotate_hue(fallback, 180.0)), color)
+            };
+
+            Ok(Theme {
+                name: file.name,
+                palette: Palette {
+                    snake_head,
+                    snake_body,
+                    rival_head: rival(file.rival_head, snake_head)?,
+                    rival_body: rival(file.rival_body, snake_body)?,
+                    coin: color(file.coin)?,
+                    pellet: color(file.pellet)?,
+                    power_ups: [
+                        color(power_ups.magnet)?,
+                        color(power_ups.ghost)?,
+                        color(power_ups.slow_motion)?,
+                        color(power_ups.score_doubler)?,
+                    ],
+                    background: color(file.background)?,
+                    ui_text: color(file.ui_text)?,
+                },
+                head_sprite: file.head_sprite,
+                body_texture: file.body_texture,
+                coin_sprite: file.coin_sprite,
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+fn desaturate(color: Color) -> Color {
+    match color.as_hsla() {
+        Color::Hsla {
+            hue,
+            saturation,
+            lightness,
+            alpha,
+        } => Color::hsla(hue, saturation * 0.25, lightness, alpha),
+        other => other,
+    }
+}
+
+fn rotate_hue(color: Color, degrees: f32) -> Color {
+    match color.as_hsla() {
+        Color::Hsla {
+            hue,
+            saturation,
+            lightness,
+            alpha,
+        } => Color::hsla(
+            (hue + degrees).rem_euclid(360.0),
+            saturation,
+            lightness,
+            alpha,
+        ),
+        other => other,
+    }
+}
+
+/// Every theme found in `assets/themes`, sorted by name once the folder has loaded.
+#[derive(Resource)]
+pub struct ThemeLibrary {
+    folder: Handle<LoadedFolder>,
+    pub themes: Vec<Handle<Theme>>,
+}
+
+impl ThemeLibrary {
+    pub fn names(&self, themes: &Assets<Theme>) -> Vec<String> {
+        self.themes
+            .iter()
+            .filter_map(|handle| themes.get(handle))
+            .map(|theme| theme.name.clone())
+            .collect()
+    }
+
+    fn find<'a>(
+        &self,
+        themes: &'a Assets<Theme>,
+        name: &str,
+    ) -> Option<(AssetId<Theme>, &'a Theme)> {
+        self.themes.iter().find_map(|handle| {
+            themes
+                .get(handle)
+                .filter(|theme| theme.name == name)
+                .map(|theme| (handle.id(), theme))
+        })
+    }
+}
+
+/// Marks UI text that keeps the color it was spawned with instead of the
+/// theme's UI text color.
+#[derive(Component)]
+pub struct OwnTextColor;
+
+/// Materials for one player's snake.
+pub struct SnakeMaterials {
+    pub head: Handle<ColorMaterial>,
+    pub body: Handle<ColorMaterial>,
+    pub body_mesh: Handle<ColorMaterial>,
+}
+
+/// Materials shared by every entity of a kind, so switching theme only touches these.
+#[derive(Resource)]
+pub struct ThemeMaterials {
+    /// The two players, then one set shared by every bot.
+    snakes: [SnakeMaterials; 3],
+    pub coin: Handle<ColorMaterial>,
+    pub pellet: Handle<ColorMaterial>,
+    /// One per power-up kind, in [`PowerUpKind::ALL`] order.
+    power_ups: [Handle<ColorMaterial>; 4],
+    /// Floor of the arena, in the theme's background color.
+    pub arena: Handle<ColorMaterial>,
+}
+
+impl FromWorld for ThemeMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let skin =
+            load_repeating_texture(world.resource::<AssetServer>(), "texture/snake_skin.png");
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        let mut snake = |color: Color| SnakeMaterials {
+            head: materials.add(color),
+            body: materials.add(color),
+            body_mesh: materials.add(ColorMaterial {
+                color,
+                texture: Some(skin.clone()),
+            }),
+        };
+        let snakes = [
+            snake(Color::GREEN),
+            snake(Color::ORANGE_RED),
+            snake(Color::GRAY),
+        ];
+        Self {
+            snakes,
+            coin: materials.add(Color::RED),
+            pellet: materials.add(Color::ORANGE),
+            power_ups: [Color::CYAN, Color::ANTIQUE_WHITE, Color::BLUE, Color::GOLD]
+                .map(|color| materials.add(color)),
+            arena: materials.add(Color::rgb(0.1, 0.1, 0.1)),
+        }
+    }
+}
+
+impl ThemeMaterials {
+    pub fn snake(&self, player: Player) -> &SnakeMaterials {
+        &self.snakes[(player.0 as usize).min(self.snakes.len() - 1)]
+    }
+
+    pub fn power_up(&self, kind: PowerUpKind) -> &Handle<ColorMaterial> {
+        &self.power_ups[kind.index()]
+    }
+}
+
+/// Loads a texture that wraps along its length, as needed for the body strip.
+fn load_repeating_texture(asset_server: &AssetServer, path: &str) -> Handle<Image> {
+    asset_server.load_with_settings(path.to_owned(), |settings: &mut ImageLoaderSettings| {
+        settings.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+            address_mode_u: ImageAddressMode::Repeat,
+            ..ImageSamplerDescriptor::linear()
+        });
+    })
+}
+
+fn load_themes(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ThemeLibrary {
+        folder: asset_server.load_folder("themes"),
+        themes: Vec::new(),
+    });
+}
+
+fn collect_themes(
+    mut folder_events: EventReader<AssetEvent<LoadedFolder>>,
+    mut library: ResMut<ThemeLibrary>,
+    folders: Res<Assets<LoadedFolder>>,
+    themes: Res<Assets<Theme>>,
+) {
+    for event in folder_events.read() {
+        if !event.is_loaded_with_dependencies(&library.folder) {
+            continue;
+        }
+        let Some(folder) = folders.get(&library.folder) else {
+            continue;
+        };
+        let mut collected: Vec<Handle<Theme>> = folder
+            .handles
+            .iter()
+            .filter_map(|handle| handle.clone().try_typed::<Theme>().ok())
+            .collect();
+        collected.sort_by_key(|handle| themes.get(handle).map(|theme| theme.name.clone()));
+        library.themes = collected;
+    }
+}
+
+/// Applies the selected theme when the selection changes, the library finishes
+/// loading, or the theme file is edited on disk.
+fn apply_theme(
+    mut theme_events: EventReader<AssetEvent<Theme>>,
+    settings: Res<Settings>,
+    library: Res<ThemeLibrary>,
+    themes: Res<Assets<Theme>>,
+    theme_materials: Res<ThemeMaterials>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut text_query: Query<&mut Text, (With<Node>, Without<OwnTextColor>)>,
+    asset_server: Res<AssetServer>,
+    mut applied: Local<Option<(String, ColorPalette, bool)>>,
+) {
+    let Some((theme_id, theme)) = library.find(&themes, &settings.theme) else {
+        return;
+    };
+    let theme_changed = theme_events.read().fold(false, |changed, event| {
+        changed || event.is_modified(theme_id) || event.is_loaded_with_dependencies(theme_id)
+    });
+    // Only the look settings matter here; volume or speed changes leave the theme alone.
+    let look_unchanged = applied
+        .as_ref()
+        .is_some_and(|(name, color_palette, high_contrast)| {
+            *name == settings.theme
+                && *color_palette == settings.color_palette
+                && *high_contrast == settings.high_contrast
+        });
+    if !theme_changed && !library.is_changed() && look_unchanged {
+        return;
+    }
+    *applied = Some((
+        settings.theme.clone(),
+        settings.color_palette,
+        settings.high_contrast,
+    ));
+    info!("Applying theme {}", theme.name);
+
+    let mut palette = theme.palette.clone();
+    settings.color_palette.apply(&mut palette);
+    if settings.high_contrast {
+        apply_high_contrast(&mut palette);
+    }
+
+    let sprite = |path: &Option<String>| path.as_ref().map(|path| asset_server.load(path.clone()));
+    let mut set_material = |handle: &Handle<ColorMaterial>, color: Color, texture| {
+        if let Some(material) = materials.get_mut(handle) {
+            material.color = color;
+            material.texture = texture;
+        }
+    };
+    let snake_colors = [
+        (palette.snake_head, palette.snake_body),
+        (palette.rival_head, palette.rival_body),
+        // Bots wear a washed-out copy of the first player's colors.
+        (
+            desaturate(palette.snake_head),
+            desaturate(palette.snake_body),
+        ),
+    ];
+    for (snake, (head, body)) in theme_materials.snakes.iter().zip(snake_colors) {
+        set_material(&snake.head, head, sprite(&theme.head_sprite));
+        set_material(&snake.body, body, None);
+        set_material(
+            &snake.body_mesh,
+            body,
+            theme
+                .body_texture
+                .as_ref()
+                .map(|path| load_repeating_texture(&asset_server, path)),
+        );
+    }
+    set_material(
+        &theme_materials.coin,
+        palette.coin,
+        sprite(&theme.coin_sprite),
+    );
+    set_material(&theme_materials.pellet, palette.pellet, None);
+    for (material, color) in theme_materials.power_ups.iter().zip(palette.power_ups) {
+        set_material(material, color, None);
+    }
+
+    set_material(&theme_materials.arena, palette.background, None);
+
+    for mut text in text_query.iter_mut() {
+        for section in text.sections.iter_mut() {
+            section.style.color = palette.ui_text;
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+
+use crate::{
+    event::{DeathCause, GameOverEvent, NewRoundEvent, SnakeDiedEvent},
+    game_state::{GameMode, GameState},
+    settings::Settings,
+    snake::{Player, SnakeHead},
+};
+
+pub struct VersusPlugin;
+
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(VersusConfig::default())
+            .insert_resource(Match::default())
+            .add_systems(Startup, setup_match_screen)
+            .add_systems(OnEnter(GameState::GameOver), show_match_screen)
+            .add_systems(OnExit(GameState::GameOver), hide_match_screen)
+            .add_systems(
+                Update,
+                (
+                    apply_game_mode,
+                    record_knockouts,
+                    record_round,
+                    click_next_round_button,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct VersusConfig {
+    /// Rounds a player has to win to take the match.
+    pub rounds_to_win: u32,
+}
+
+impl Default for VersusConfig {
+    fn default() -> Self {
+        Self { rounds_to_win: 3 }
+    }
+}
+
+/// Rounds won by each player in the current versus match.
+#[derive(Resource, Default)]
+pub struct Match {
+    pub wins: [u32; 2],
+    /// Rounds played so far, draws included.
+    pub round: u32,
+    /// Winner of the last round, or `None` if both snakes died together.
+    pub last_winner: Option<Player>,
+    /// The last snake to be cut off this round, and who by.
+    pub last_knockout: Option<(Player, Player)>,
+}
+
+impl Match {
+    pub fn champion(&self, config: &VersusConfig) -> Option<Player> {
+        self.wins
+            .iter()
+            .position(|wins| *wins >= config.rounds_to_win)
+            .map(|index| Player(index as u8))
+    }
+}
+
+#[derive(Component)]
+struct MatchScreen;
+
+#[derive(Component)]
+struct MatchTitleText;
+
+#[derive(Component)]
+struct MatchScoreText;
+
+#[derive(Component)]
+struct NextRoundButton;
+
+#[derive(Component)]
+struct NextRoundButtonText;
+
+/// Starts a fresh match whenever the game mode is switched in the settings.
+fn apply_game_mode(
+    settings: Res<Settings>,
+    mut last_mode: Local<Option<GameMode>>,
+    mut versus_match: ResMut<Match>,
+    mut new_round_event: EventWriter<NewRoundEvent>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let previous = last_mode.replace(settings.game_mode);
+    if previous.is_none() || previous == Some(settings.game_mode) {
+        return;
+    }
+    *versus_match = Match::default();
+    new_round_event.send(NewRoundEvent);
+    if state.get() == &GameState::GameOver {
+        next_state.set(GameState::InGame);
+    }
+}
+
+fn record_knockouts(
+    mut snake_died_event: EventReader<SnakeDiedEvent>,
+    mut versus_match: ResMut<Match>,
+) {
+    for event in snake_died_event.read() {
+        if let DeathCause::Snake(killer) = event.cause {
+            versus_match.last_knockout = Some((killer, event.player));
+        }
+    }
+}
+
+/// Credits the round to the last snake standing once it ends.
+fn record_round(
+    mut game_over_event: EventReader<GameOverEvent>,
+    mut versus_match: ResMut<Match>,
+    head_query: Query<&Player, With<SnakeHead>>,
+    settings: Res<Settings>,
+) {
+    for _ in game_over_event.read() {
+        if settings.game_mode != GameMode::Versus {
+            continue;
+        }
+        let survivors: Vec<&Player> = head_query
+            .iter()
+            .filter(|player| !player.is_bot())
+            .collect();
+        let winner = match survivors.as_slice() {
+            [winner] => Some(**winner),
+            _ => None,
+        };
+        if let Some(winner) = winner {
+            versus_match.wins[winner.0 as usize % 2] += 1;
+        }
+        versus_match.round += 1;
+        versus_match.last_winner = winner;
+    }
+}
+
+fn show_match_screen(
+    settings: Res<Settings>,
+    versus_match: Res<Match>,
+    config: Res<VersusConfig>,
+    mut screen_query: Query<&mut Visibility, With<MatchScreen>>,
+    mut title_query: Query<
+        &mut Text,
+        (
+            With<MatchTitleText>,
+            Without<MatchScoreText>,
+            Without<NextRoundButtonText>,
+        ),
+    >,
+    mut score_query: Query<&mut Text, (With<MatchScoreText>, Without<NextRoundButtonText>)>,
+    mut button_text_query: Query<&mut Text, With<NextRoundButtonText>>,
+) {
+    if settings.game_mode != GameMode::Versus {
+        return;
+    }
+    let champion = versus_match.champion(&config);
+    let knockout = versus_match
+        .last_knockout
+        .map(|(killer, victim)| format!("{} cut off {}\n", killer.label(), victim.label()))
+        .unwrap_or_default();
+    let title = match (champion, versus_match.last_winner) {
+        (Some(champion), _) => format!("{} wins the match!", champion.label()),
+        (None, Some(winner)) => format!("{} wins round {}", winner.label(), versus_match.round),
+        (None, None) => format!("Round {} is a draw", versus_match.round),
+    };
+    for mut text in title_query.iter_mut() {
+        text.sections[0].value = title.clone();
+    }
+    for mut text in score_query.iter_mut() {
+        text.sections[0].value = format!(
+            "{knockout}P1 {}  -  {} P2   (first to {})",
+            versus_match.wins[0], versus_match.wins[1], config.rounds_to_win
+        );
+    }
+    for mut text in button_text_query.iter_mut() {
+        text.sections[0].value = if champion.is_some() {
+            "New Match".to_string()
+        } else {
+            "Next Round".to_string()
+        };
+    }
+    for mut visibility in screen_query.iter_mut() {
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn hide_match_screen(mut screen_query: Query<&mut Visibility, With<MatchScreen>>) {
+    for mut visibility in screen_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn click_next_round_button(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<NextRoundButton>)>,
+    mut versus_match: ResMut<Match>,
+    config: Res<VersusConfig>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut new_round_event: EventWriter<NewRoundEvent>,
+) {
+    for interaction in interaction_query.iter() {
+        if interaction != &Interaction::Pressed {
+            continue;
+        }
+        if versus_match.champion(&config).is_some() {
+            *versus_match = Match::default();
+        }
+        versus_match.last_knockout = None;
+        next_state.set(GameState::InGame);
+        new_round_event.send(NewRoundEvent);
+    }
+}
+
+fn setup_match_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("font/FiraSans-Bold.ttf");
+    let text_style = |font_size| TextStyle {
+        font: font.clone(),
+        font_size,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(16.0),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            MatchScreen,
+            Name::new("MatchScreen"),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", text_style(48.0)),
+                MatchTitleText,
+            ));
+            parent.spawn((
+                TextBundle::from_section("", text_style(28.0)),
+                MatchScoreText,
+            ));
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(250.0),
+                            height: Val::Px(65.0),
+                            border: UiRect::all(Val::Px(5.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        border_color: BorderColor(Color::WHITE),
+                        background_color: Color::rgb(0.15, 0.15, 0.15).into(),
+                        ..default()
+                    },
+                    NextRoundButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section("Next Round", text_style(40.0)),
+                        NextRoundButtonText,
+                    ));
+                });
+        });
+}